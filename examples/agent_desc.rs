@@ -1,8 +1,6 @@
-use rig::client::Nothing;
 use rig::completion::Prompt;
 use rig::prelude::*;
-use rig::providers::ollama;
-use rig::providers::ollama::Client;
+use rig_test::helper::{ConfigError, ProviderClient, client};
 use serde::{Deserialize, Serialize};
 
 // Structures
@@ -75,21 +73,16 @@ fn update_image(id: &str, description: String) -> Result<(), anyhow::Error> {
 
 // Agent for handling images
 struct ImageDescriptionAgent {
-    client: Client,
+    client: ProviderClient,
     model: String,
 }
 
 impl ImageDescriptionAgent {
-    fn new(model: &str) -> Self {
-        let client: ollama::Client = ollama::Client::builder()
-            .api_key(Nothing)
-            .base_url("http://localhost:8050")
-            .build()
-            .unwrap();
-        Self {
-            client,
+    fn new(model: &str) -> Result<Self, ConfigError> {
+        Ok(Self {
+            client: client(false)?,
             model: model.to_string(),
-        }
+        })
     }
 
     async fn generate_description(
@@ -206,7 +199,7 @@ async fn main() -> Result<(), anyhow::Error> {
     println!("🚀 Starting Image Description Agent\n");
 
     // Create agent
-    let agent = ImageDescriptionAgent::new("qwen3:14b");
+    let agent = ImageDescriptionAgent::new("qwen3:14b")?;
 
     // Request data
     let req_data = ReqData {
@@ -230,7 +223,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_agent_with_single_uuid() {
-        let agent = ImageDescriptionAgent::new("qwen3:14b");
+        let agent = ImageDescriptionAgent::new("qwen3:14b").unwrap();
 
         let req_data = ReqData {
             uuid_old: Some("2025-12-02".to_string()),
@@ -246,7 +239,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_agent_with_both_uuids() {
-        let agent = ImageDescriptionAgent::new("qwen3:14b");
+        let agent = ImageDescriptionAgent::new("qwen3:14b").unwrap();
 
         let req_data = ReqData {
             uuid_old: Some("2025-12-02".to_string()),
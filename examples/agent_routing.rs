@@ -6,9 +6,16 @@ use std::time::Instant;
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     let is_local = false;
-    let client = client(is_local);
-    let tool_model = REMOTE_MODELS[9];
-    let target_model = REMOTE_MODELS[0];
+    let client = client(is_local)?;
+    let registry = ModelRegistry::builtin();
+    let tool_model = "functiongemma";
+    registry.require(tool_model, is_local.into(), &[Capability::Tools])?;
+    let target_model = &registry
+        .query()
+        .host(is_local.into())
+        .with(Capability::Reasoning)
+        .first()?
+        .name;
 
     // Note that you can also create your own semantic router for this
     // that uses a vector store under the hood
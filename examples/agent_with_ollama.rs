@@ -14,7 +14,6 @@ async fn main() -> Result<(), anyhow::Error> {
     // In the case of ollama, no API key is necessary, so we can use the `Nothing` struct in its
     // place
     let client: ollama::Client = ollama::Client::new(Nothing).unwrap();
-    //ollama::Client::builder() .api_key(Nothing) .base_url("http://localhost:8050") .build() .unwrap();
 
     // Create agent with a single context prompt
     let comedian_agent = client
@@ -1,19 +1,15 @@
-use rig::client::Nothing;
 use rig::completion::Prompt;
 use rig::prelude::*;
-use rig::providers::ollama;
+use rig_test::helper::{default_client, default_model};
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
-    let client: ollama::Client = ollama::Client::builder()
-        .api_key(Nothing) // do not allow any except Nothing
-        //.headers(headers) // not in ollama
-        .base_url("http://localhost:8050")
-        .build()
-        .unwrap();
+    // The default endpoint of endpoints.json or RIG_TEST_ENDPOINT, and its model if it names one
+    let client = default_client()?;
+    let model = default_model("llama3.2-vision")?;
     // Create agent with a single context prompt
     let comedian_agent = client
-        .agent("llama3.2-vision")
+        .agent(&model)
         .preamble("You are a comedian here to entertain the user using humour and jokes.")
         .build();
 
@@ -1,7 +1,7 @@
 use rig_test::prompt_context::ContextParser;
 
 fn main() {
-    let mut parser = ContextParser::new();
+    let parser = ContextParser::new();
 
     match parser.parse("en", "Detect changes during last two weeks") {
         Ok(context) => {
@@ -18,11 +18,12 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
+    use rig_test::lang::Fallback;
     use rig_test::prompt_context::*;
 
     #[test]
     fn test_basic_parsing() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser
             .parse("en", "Detect changes during last two weeks")
             .unwrap();
@@ -35,7 +36,7 @@ mod tests {
 
     #[test]
     fn test_amount_numeric() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser.parse("en", "Show 5 reports").unwrap();
 
         assert_eq!(result.amount(), Some(5));
@@ -43,7 +44,7 @@ mod tests {
 
     #[test]
     fn test_amount_text() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser.parse("en", "Show three reports").unwrap();
 
         assert_eq!(result.amount(), Some(3));
@@ -51,7 +52,7 @@ mod tests {
 
     #[test]
     fn test_period_detection() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
 
         let test_cases = vec![
             ("last day", Period::Day),
@@ -74,12 +75,12 @@ mod tests {
 
     #[test]
     fn test_multiple_keys() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser
             .parse("en", "Build a new report for last month")
             .unwrap();
 
-        assert!(!result.has_key(PromptKey::Object));
+        assert!(result.has_key(PromptKey::Object));
         assert!(result.has_key(PromptKey::New));
         assert!(result.has_key(PromptKey::Document));
         assert!(result.has_key(PromptKey::Last));
@@ -88,7 +89,7 @@ mod tests {
 
     #[test]
     fn test_no_matches() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser.parse("en", "hello world").unwrap();
 
         assert!(result.keys().is_empty());
@@ -97,19 +98,24 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn test_unsupported_language() {
-        let mut parser = ContextParser::new();
-        const GREEN: &str = "\x1b[92m";
-        const RESET: &str = "\x1b[0m";
-        println!("\n     {}!!! There should be panic here. !!!{}", GREEN, RESET);
+        let parser = ContextParser::new();
         let result = parser.parse("ru", "test");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(ParserError::UnsupportedLanguage(lang)) if lang == "ru"));
+    }
+
+    #[test]
+    fn test_fallback_language() {
+        let parser = ContextParser::new().with_fallback(Fallback::Language("en".to_string()));
+        let result = parser.parse("ru", "last week").unwrap();
+
+        assert!(result.has_key(PromptKey::Last));
+        assert_eq!(result.period(), Some(Period::Week));
     }
 
     #[test]
     fn test_duplicate_keys_not_added() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser.parse("en", "new new new report").unwrap();
 
         let new_count = result
@@ -122,19 +128,197 @@ mod tests {
 
     #[test]
     fn test_case_insensitive() {
-        let mut parser = ContextParser::new();
-        //       let result1 = parser.parse("en", "LAST WEEK").unwrap();
+        let parser = ContextParser::new();
+        let result1 = parser.parse("en", "LAST WEEK").unwrap();
         let result2 = parser.parse("en", "last week").unwrap();
 
-        // Aho-Corasick by default case-sensitive, but can check basic functionality
         assert!(result2.has_key(PromptKey::Last));
         assert_eq!(result2.period(), Some(Period::Week));
-        //        assert_eq!(result1, result2);
+        assert_eq!(result1.keys(), result2.keys());
+        assert_eq!(result1.period(), result2.period());
+        assert_eq!(result1.matches()[0].surface(), "LAST");
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let parser = ContextParser::new();
+
+        let result = parser.parse("en", "renew the newsletter").unwrap();
+        assert!(!result.has_key(PromptKey::New));
+
+        let result = parser.parse("en", "What happened today and on Monday?").unwrap();
+        assert_eq!(result.period(), None);
+
+        // "10" is not found inside "100"
+        let result = parser.parse("en", "Show 100 photos").unwrap();
+        assert_eq!(result.amounts(), vec![100]);
+    }
+
+    #[test]
+    fn test_prefix_stems() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("en", "Comparing the differences between buildings over 3 Weeks")
+            .unwrap();
+
+        assert!(result.has_key(PromptKey::Comparison));
+        assert!(result.has_key(PromptKey::Object));
+        assert_eq!(result.period(), Some(Period::Week));
+        assert_eq!(result.amount(), Some(3));
+    }
+
+    #[test]
+    fn test_umlaut_folding() {
+        let parser = ContextParser::new();
+        let expected = vec![DateConstraint::On(DateValue::DayMonth { day: 3, month: 3 })];
+
+        assert_eq!(parser.parse_dates("de", "Fotos vom 3. MÄRZ").unwrap(), expected);
+        assert_eq!(parser.parse_dates("de", "Fotos vom 3. Maerz").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_all_matches_with_spans() {
+        let parser = ContextParser::new();
+        let prompt = "Compare LAST week with last Months";
+        let result = parser.parse("en", prompt).unwrap();
+
+        assert_eq!(result.periods(), vec![Period::Week, Period::Month]);
+        assert_eq!(result.period(), Some(Period::Week));
+
+        let last: Vec<&str> = result
+            .key_matches(PromptKey::Last)
+            .map(|m| m.surface())
+            .collect();
+        assert_eq!(last, vec!["LAST", "last"]);
+
+        let comparison = result.key_matches(PromptKey::Comparison).next().unwrap();
+        assert_eq!(comparison.span(), 0..7);
+        assert_eq!(comparison.surface(), "Compare");
+
+        let months = result.matches().last().unwrap();
+        assert_eq!(months.term(), Term::Period(Period::Month));
+        assert_eq!(&prompt[months.span()], "Months");
+
+        let starts: Vec<usize> = result.matches().iter().map(|m| m.span().start).collect();
+        assert!(starts.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_all_amounts() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("en", "Compare two reports of the last 3 weeks")
+            .unwrap();
+
+        assert_eq!(result.amounts(), vec![2, 3]);
+        // numbers written as digits win
+        assert_eq!(result.amount(), Some(3));
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let parser = std::sync::Arc::new(ContextParser::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let parser = parser.clone();
+                std::thread::spawn(move || parser.parse("en", "Compare last two weeks").unwrap())
+            })
+            .collect();
+
+        for handle in handles {
+            let result = handle.join().unwrap();
+            assert!(result.has_key(PromptKey::Comparison));
+            assert_eq!(result.amount(), Some(2));
+        }
+    }
+
+    #[test]
+    fn test_amount_numerals_en() {
+        let parser = ContextParser::new();
+        let test_cases = vec![
+            ("last 12 days", 12),
+            ("twenty reports", 20),
+            ("Show twenty-two images", 22),
+            ("one hundred and five photos", 105),
+            ("two thousand three hundred files", 2300),
+            ("the third report", 3),
+            ("the twenty-first image", 21),
+            ("a couple of weeks", 2),
+            ("a dozen photos", 12),
+            ("Compare a few reports", 3),
+            ("Show 1,000 photos", 1000),
+            ("Compare the 1,200,000 files", 1_200_000),
+        ];
+
+        for (prompt, expected) in test_cases {
+            let result = parser.parse("en", prompt).unwrap();
+            assert_eq!(result.amount(), Some(expected), "Failed for: {}", prompt);
+        }
+    }
+
+    #[test]
+    fn test_amount_numerals_de() {
+        let parser = ContextParser::new().with_fallback(Fallback::Language("en".to_string()));
+        let test_cases = vec![
+            ("zweiundzwanzig Berichte", 22),
+            ("die letzten zwölf Tage", 12),
+            ("einhundertfünf Bilder", 105),
+            ("dreißig Fotos", 30),
+            ("Zeige 1.000 Fotos", 1000),
+            ("der dritte Bericht", 3),
+            ("am einundzwanzigsten Tag", 21),
+            ("ein Dutzend Fotos", 12),
+        ];
+
+        for (prompt, expected) in test_cases {
+            let result = parser.parse("de", prompt).unwrap();
+            assert_eq!(result.amount(), Some(expected), "Failed for: {}", prompt);
+        }
+
+        // the article is not a number
+        let result = parser.parse("de", "ein Bild und eine Woche").unwrap();
+        assert_eq!(result.amount(), None);
+
+        // nor is an ordinal that orders steps
+        for prompt in ["Erste vergleichen, dann beschreiben", "zum ersten Mal"] {
+            let result = parser.parse("de", prompt).unwrap();
+            assert_eq!(result.amount(), None, "Failed for: {}", prompt);
+        }
+    }
+
+    #[test]
+    fn test_sequencing_ordinals_en() {
+        let parser = ContextParser::new();
+        for prompt in [
+            "first compare then describe",
+            "Compare first, then describe the photos",
+            "Describe the images; second, compare them",
+        ] {
+            let result = parser.parse("en", prompt).unwrap();
+            assert_eq!(result.amount(), None, "Failed for: {}", prompt);
+            assert!(result.has_key(PromptKey::Comparison), "Failed for: {}", prompt);
+        }
+        let result = parser.parse("en", "Compare my first two reports").unwrap();
+        assert_eq!(result.amounts(), vec![2]);
+        let result = parser.parse("en", "Describe the first photo").unwrap();
+        assert_eq!(result.amount(), Some(1));
+    }
+
+    #[test]
+    fn test_amount_not_from_dates() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("en", "Show photos from 15 December 2025 and 2025-12-02")
+            .unwrap();
+        assert_eq!(result.amount(), None);
+
+        let result = parser.parse("en", "Show 4 photos since the 1st").unwrap();
+        assert_eq!(result.amounts(), vec![4]);
     }
 
     #[test]
     fn test_complex_prompt() {
-        let mut parser = ContextParser::new();
+        let parser = ContextParser::new();
         let result = parser
             .parse(
                 "en",
@@ -149,5 +333,506 @@ mod tests {
         assert_eq!(result.period(), Some(Period::Quarter));
         assert_eq!(result.amount(), Some(3));
     }
-}
 
+    #[test]
+    fn test_basic_parsing_de() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("de", "Erkenne Änderungen der letzten zwei Wochen")
+            .unwrap();
+
+        assert!(result.has_key(PromptKey::Comparison));
+        assert!(result.has_key(PromptKey::Last));
+        assert_eq!(result.period(), Some(Period::Week));
+        assert_eq!(result.amount(), Some(2));
+    }
+
+    #[test]
+    fn test_regional_de() {
+        let parser = ContextParser::new();
+        for lang in ["de-CH", "de-AT"] {
+            let result = parser.parse(lang, "Vergleiche die letzten drei Wochen").unwrap();
+            assert!(result.has_key(PromptKey::Comparison), "Failed for: {}", lang);
+            assert_eq!(result.period(), Some(Period::Week));
+            assert_eq!(result.amount(), Some(3));
+        }
+        assert!(matches!(
+            parser.parse("ru-RU", "test"),
+            Err(ParserError::UnsupportedLanguage(_))
+        ));
+    }
+
+    #[test]
+    fn test_amount_de() {
+        let parser = ContextParser::new();
+        let result = parser.parse("de", "Zeige 5 Berichte").unwrap();
+        assert_eq!(result.amount(), Some(5));
+
+        let result = parser.parse("de", "Zeige drei Berichte").unwrap();
+        assert_eq!(result.amount(), Some(3));
+    }
+
+    #[test]
+    fn test_period_detection_de() {
+        let parser = ContextParser::new();
+
+        let test_cases = vec![
+            ("letzter Tag", Period::Day),
+            ("diese Woche", Period::Week),
+            ("aktueller Monat", Period::Month),
+            ("letztes Quartal", Period::Quarter),
+            ("dieses Jahr", Period::Year),
+        ];
+
+        for (prompt, expected_period) in test_cases {
+            let result = parser.parse("de", prompt).unwrap();
+            assert_eq!(
+                result.period(),
+                Some(expected_period),
+                "Failed for: {}",
+                prompt
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiple_keys_de() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("de", "Erstelle einen neuen Bericht für den letzten Monat")
+            .unwrap();
+
+        assert!(result.has_key(PromptKey::Object));
+        assert!(result.has_key(PromptKey::New));
+        assert!(result.has_key(PromptKey::Document));
+        assert!(result.has_key(PromptKey::Last));
+        assert_eq!(result.period(), Some(Period::Month));
+    }
+
+    #[test]
+    fn test_no_matches_de() {
+        let parser = ContextParser::new();
+        let result = parser.parse("de", "hallo Welt").unwrap();
+
+        assert!(result.keys().is_empty());
+        assert_eq!(result.period(), None);
+        assert_eq!(result.amount(), None);
+    }
+
+    #[test]
+    fn test_duplicate_keys_and_case_de() {
+        let parser = ContextParser::new();
+        let result = parser.parse("de", "neu neu neu Bericht").unwrap();
+        assert_eq!(
+            result.keys().iter().filter(|k| **k == PromptKey::New).count(),
+            1
+        );
+
+        let result1 = parser.parse("de", "LETZTE WOCHE").unwrap();
+        let result2 = parser.parse("de", "letzte Woche").unwrap();
+        assert_eq!(result1.keys(), result2.keys());
+        assert_eq!(result1.period(), result2.period());
+    }
+
+    #[test]
+    fn test_word_boundaries_de() {
+        let parser = ContextParser::new();
+        // "erneuern" and "neun" must not count as "neu"
+        let result = parser.parse("de", "neun Leute erneuern das Dach").unwrap();
+        assert!(!result.has_key(PromptKey::New));
+        assert_eq!(result.amount(), Some(9));
+    }
+
+    #[test]
+    fn test_prefix_stems_de() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("de", "Vergleiche die Unterschiede der Gebäude über 3 Wochen")
+            .unwrap();
+
+        assert!(result.has_key(PromptKey::Comparison));
+        assert!(result.has_key(PromptKey::Object));
+        assert_eq!(result.period(), Some(Period::Week));
+        assert_eq!(result.amount(), Some(3));
+    }
+
+    #[test]
+    fn test_negation_de() {
+        let parser = ContextParser::new();
+        let result = parser.parse("de", "Zeige alles außer Dokumente").unwrap();
+        assert!(!result.has_key(PromptKey::Document));
+        assert_eq!(result.excluded_keys(), &[PromptKey::Document]);
+
+        let result = parser
+            .parse("de", "Nicht vergleichen, sondern die Bilder beschreiben")
+            .unwrap();
+        assert!(result.has_key(PromptKey::Document));
+        assert!(result.has_key(PromptKey::Description));
+        assert!(result.is_excluded(PromptKey::Comparison));
+    }
+
+    #[test]
+    fn test_negation_after_verb_de() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("de", "Vergleiche nicht, beschreibe nur")
+            .unwrap();
+        assert_eq!(result.keys(), &[PromptKey::Description]);
+        assert!(result.is_excluded(PromptKey::Comparison));
+
+        let result = parser.parse("de", "Vergleiche nicht").unwrap();
+        assert!(!result.has_key(PromptKey::Comparison));
+        assert_eq!(result.excluded_keys(), &[PromptKey::Comparison]);
+
+        // Something negatable follows, so "nicht" still looks ahead
+        let result = parser
+            .parse("de", "Beschreibe die Bilder, nicht die Dokumente")
+            .unwrap();
+        assert!(result.has_key(PromptKey::Description));
+        assert!(!result.is_excluded(PromptKey::Description));
+    }
+
+    #[test]
+    fn test_complex_prompt_de() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse(
+                "de",
+                "Vergleiche alle Änderungen in Dokumenten der letzten drei Quartale",
+            )
+            .unwrap();
+
+        assert!(result.has_key(PromptKey::Comparison));
+        assert!(result.has_key(PromptKey::All));
+        assert!(result.has_key(PromptKey::Document));
+        assert!(result.has_key(PromptKey::Last));
+        assert_eq!(result.period(), Some(Period::Quarter));
+        assert_eq!(result.amount(), Some(3));
+    }
+
+    #[test]
+    fn test_locales_complete() {
+        let parser = ContextParser::new();
+        let required = ContextParser::required_messages();
+        assert!(required.contains(&"object-words".to_string()));
+        assert!(required.contains(&"negation-words".to_string()));
+
+        for lang in rig_test::lang::TextManager::new().languages() {
+            assert_eq!(
+                parser.missing_messages(&lang),
+                Vec::<String>::new(),
+                "Incomplete locale: {}",
+                lang
+            );
+        }
+        assert_eq!(parser.missing_messages("ru"), required);
+    }
+
+    #[test]
+    fn test_negation_excludes_keys() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("en", "Show everything except documents")
+            .unwrap();
+        assert!(!result.has_key(PromptKey::Document));
+        assert_eq!(result.excluded_keys(), &[PromptKey::Document]);
+
+        let result = parser
+            .parse("en", "Don’t compare, just describe the images")
+            .unwrap();
+        assert_eq!(result.keys(), &[PromptKey::Document, PromptKey::Description]);
+        assert!(result.is_excluded(PromptKey::Comparison));
+
+        // Mentioned once negated and once not: still a key
+        let result = parser
+            .parse("en", "Describe the report, not the video report")
+            .unwrap();
+        assert!(result.has_key(PromptKey::Document));
+        assert!(result.excluded_keys().is_empty());
+    }
+
+    #[test]
+    fn test_negation_scope_ends_at_contrast() {
+        let parser = ContextParser::new();
+        let result = parser
+            .parse("en", "not the last week but this month")
+            .unwrap();
+        assert!(result.is_excluded(PromptKey::Last));
+        assert_eq!(result.period(), Some(Period::Month));
+        assert_eq!(result.periods(), vec![Period::Month]);
+
+        let negated: Vec<&str> = result
+            .matches()
+            .iter()
+            .filter(|m| m.is_negated())
+            .map(|m| m.surface())
+            .collect();
+        assert_eq!(negated, vec!["last", "week"]);
+
+        let result = parser
+            .parse("en", "Show 3 reports without the 2 videos")
+            .unwrap();
+        assert_eq!(result.amount(), Some(3));
+        assert_eq!(result.amounts(), vec![3]);
+    }
+
+    #[test]
+    fn test_vocabulary_from_file() {
+        let vocabulary = Vocabulary::from_file("rules/construction.json").unwrap();
+        let parser = ContextParser::new().with_vocabulary(vocabulary);
+        let result = parser
+            .parse("en", "Compare the windows and radiators on the last drawings")
+            .unwrap();
+        assert_eq!(result.custom_keys(), &["radiator", "window"]);
+        assert!(result.has_custom_key("window"));
+        assert!(!result.has_custom_key("door"));
+        // "drawing" extends the built-in key
+        assert!(result.has_key(PromptKey::Document));
+        assert!(result.has_key(PromptKey::Comparison));
+
+        let surfaces: Vec<&str> = result
+            .matches()
+            .iter()
+            .filter(|m| matches!(m.term(), Term::Custom(_)))
+            .map(|m| m.surface())
+            .collect();
+        assert_eq!(surfaces, vec!["windows", "radiators"]);
+
+        let result = parser.parse("en", "Show the openings, not the doors").unwrap();
+        assert_eq!(result.custom_keys(), &["opening"]);
+        assert_eq!(result.excluded_custom_keys(), &["door"]);
+    }
+
+    #[test]
+    fn test_vocabulary_from_ftl() {
+        let ftl = "
+radiator-words = Heizkörper* Heizung*
+window-words = Fenster*
+match-prefix = true
+";
+        let vocabulary = Vocabulary::from_ftl("de", ftl).unwrap();
+        assert_eq!(vocabulary.names().collect::<Vec<_>>(), vec!["radiator", "window"]);
+        assert_eq!(vocabulary.words("window", "de"), &["Fenster*"]);
+
+        let parser = ContextParser::new()
+            .with_fallback(Fallback::Language("en".to_string()))
+            .with_vocabulary(vocabulary)
+            .with_vocabulary(Vocabulary::new().with_words("door", "de", ["Tür", "Türen"]));
+        let result = parser
+            .parse("de", "Zeige die Fenster, Türen und Heizkörper im Bad")
+            .unwrap();
+        assert_eq!(result.custom_keys(), &["door", "radiator", "window"]);
+
+        // Regional variants inherit the words of the language
+        let result = parser.parse("de-CH", "Zeige die Fenster und Türen").unwrap();
+        assert_eq!(result.custom_keys(), &["door", "window"]);
+        let parser =
+            parser.with_vocabulary(Vocabulary::new().with_words("door", "de-CH", ["Porte"]));
+        assert_eq!(
+            parser.vocabulary().words("door", "de-CH"),
+            &["Porte", "Tür", "Türen"]
+        );
+        let result = parser.parse("de-AT", "Zeige die Porte").unwrap();
+        assert!(result.custom_keys().is_empty());
+
+        // Registered words of another language do not leak
+        let result = parser.parse("en", "Show the windows").unwrap();
+        assert!(result.custom_keys().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_vocabulary() {
+        let parser =
+            ContextParser::new().with_vocabulary(Vocabulary::new().with_words("gap", "en", ["*"]));
+        assert!(matches!(
+            parser.parse("en", "Show gaps"),
+            Err(ParserError::InvalidPattern(name)) if name == "gap"
+        ));
+        assert!(matches!(
+            Vocabulary::from_json(r#"{ "door": ["door"] }"#),
+            Err(ParserError::InvalidVocabulary(_))
+        ));
+        assert!(matches!(
+            Vocabulary::from_file("missing/vocabulary.json"),
+            Err(ParserError::InvalidVocabulary(_))
+        ));
+
+        // Names of the keys the parser fills itself are reserved
+        assert!(matches!(
+            Vocabulary::from_json(r#"{ "Period": { "en": ["sprint"] } }"#),
+            Err(ParserError::InvalidVocabulary(message)) if message.contains("Period")
+        ));
+        assert!(matches!(
+            Vocabulary::from_ftl("en", "amount-words = dozen"),
+            Err(ParserError::InvalidVocabulary(_))
+        ));
+        let parser = ContextParser::new()
+            .with_vocabulary(Vocabulary::new().with_words("Amount", "en", ["dozen"]));
+        assert!(matches!(
+            parser.parse("en", "Show a dozen photos"),
+            Err(ParserError::InvalidVocabulary(_))
+        ));
+    }
+
+    #[test]
+    fn test_fuzzy_matching() {
+        let fuzzy = ContextParser::new().with_match_mode(MatchMode::Fuzzy);
+        let prompt = "comapre the reprot of last wekk";
+
+        // Exact is the default and ignores typos
+        let result = ContextParser::new().parse("en", prompt).unwrap();
+        assert_eq!(result.keys(), &[PromptKey::Last]);
+        assert_eq!(result.period(), None);
+
+        let result = fuzzy.parse("en", prompt).unwrap();
+        assert!(result.has_key(PromptKey::Comparison));
+        assert!(result.has_key(PromptKey::Document));
+        assert_eq!(result.period(), Some(Period::Week));
+
+        let corrections: Vec<(&str, &str)> = result
+            .corrections()
+            .map(|m| (m.surface(), m.correction().unwrap()))
+            .collect();
+        assert_eq!(
+            corrections,
+            vec![("comapre", "compare"), ("reprot", "report"), ("wekk", "week")]
+        );
+        for m in result.corrections() {
+            assert!(m.confidence() < 1.0 && m.confidence() > 0.5);
+        }
+        let last = result.key_matches(PromptKey::Last).next().unwrap();
+        assert_eq!(last.correction(), None);
+        assert_eq!(last.confidence(), 1.0);
+    }
+
+    #[test]
+    fn test_fuzzy_bounds() {
+        let fuzzy = ContextParser::new().with_match_mode(MatchMode::Fuzzy);
+
+        // Stems keep the rest of the word
+        let result = fuzzy.parse("en", "Comapring the pictrues").unwrap();
+        let corrections: Vec<&str> = result.corrections().filter_map(|m| m.correction()).collect();
+        assert_eq!(corrections, vec!["comparing", "pictures"]);
+
+        // Short words and other first letters are not corrected
+        let result = fuzzy.parse("en", "Show the das and a seek").unwrap();
+        assert!(result.matches().is_empty());
+
+        // Two edits only for long words
+        let result = fuzzy.parse("en", "Descirbe the modfiications").unwrap();
+        assert_eq!(result.keys(), &[PromptKey::Description]);
+        assert_eq!(result.corrections().count(), 2);
+        let result = fuzzy.parse("en", "wkke").unwrap();
+        assert_eq!(result.period(), None);
+
+        // Dates and numbers are not taken for typos
+        let result = fuzzy.parse("en", "Show five documents from Monday").unwrap();
+        assert_eq!(result.corrections().count(), 0);
+
+        // Nor are real words close to a keyword, or words that only share a short stem
+        for prompt in [
+            "The signal is weak, who made it?",
+            "List the lost files",
+            "Is it a fine film, ever?",
+            "Describe the damage to the dairy",
+            "Who is the mate of the moth?",
+            "I resent the changes",
+        ] {
+            let result = fuzzy.parse("en", prompt).unwrap();
+            assert_eq!(result.corrections().count(), 0, "Failed for: {}", prompt);
+        }
+        let fuzzy_de = ContextParser::new()
+            .with_fallback(Fallback::Language("en".to_string()))
+            .with_match_mode(MatchMode::Fuzzy);
+        for prompt in ["Zeige jene Fotos, die alte Forderung", "Ich bin bald dabei", "Was hat er gesagt?"] {
+            let result = fuzzy_de.parse("de", prompt).unwrap();
+            assert_eq!(result.corrections().count(), 0, "Failed for: {}", prompt);
+        }
+        // Regional languages use the word list of their parent
+        let result = fuzzy_de.parse("de-CH", "Zeige jene Fotos").unwrap();
+        assert_eq!(result.corrections().count(), 0);
+        let result = fuzzy_de.parse("de", "Vergliche die Bilder").unwrap();
+        assert!(result.has_key(PromptKey::Comparison));
+    }
+
+    #[test]
+    fn test_stemming_en() {
+        let parser = ContextParser::new().with_vocabulary(
+            Vocabulary::new()
+                .with_words("window", "en", ["window"])
+                .with_words("radiator", "en", ["radiator", "heating"]),
+        );
+        // Dictionary forms match their inflections, "make" is not a stem in the FTL file
+        let result = parser
+            .parse("en", "Makes a picture of the Windows and the radiators' heating")
+            .unwrap();
+        assert!(result.has_key(PromptKey::Object));
+        assert_eq!(result.custom_keys(), &["radiator", "window"]);
+        let surfaces: Vec<&str> = result
+            .matches()
+            .iter()
+            .filter(|m| matches!(m.term(), Term::Custom(_)))
+            .map(|m| m.surface())
+            .collect();
+        assert_eq!(surfaces, vec!["Windows", "radiators", "heating"]);
+
+        // Stems still do not cross word boundaries
+        let result = parser.parse("en", "widow heaters").unwrap();
+        assert!(result.custom_keys().is_empty());
+    }
+
+    #[test]
+    fn test_stemming_de() {
+        let parser = ContextParser::new()
+            .with_fallback(Fallback::Language("en".to_string()))
+            .with_vocabulary(
+                Vocabulary::new().with_words("Comparison", "de", ["vergleichen", "Unterschied"]),
+            );
+        for prompt in [
+            "Vergleiche die Bilder",
+            "Ich habe die Bilder verglichen",
+            "Zeige die Unterschiede",
+            "Der Vergleich der letzten Wochen",
+        ] {
+            let result = parser.parse("de", prompt).unwrap();
+            assert!(
+                result.has_key(PromptKey::Comparison),
+                "Failed for: {}",
+                prompt
+            );
+        }
+        let result = parser.parse("de", "Der Vergleich der letzten Wochen").unwrap();
+        assert_eq!(result.period(), Some(Period::Week));
+    }
+
+    #[test]
+    fn test_auto_language() {
+        let parser = ContextParser::new().with_fallback(Fallback::Language("en".to_string()));
+        let result = parser
+            .parse(AUTO_LANGUAGE, "Compare the reports of the last two weeks")
+            .unwrap();
+        assert!(result.has_key(PromptKey::Comparison));
+        assert_eq!(result.amount(), Some(2));
+
+        // German numerals and dates only parse with the German locale
+        let dates = parser
+            .parse_dates(AUTO_LANGUAGE, "Zeige mir die Fotos von vor zwei Wochen")
+            .unwrap();
+        assert_eq!(
+            dates,
+            vec![DateConstraint::On(DateValue::Relative {
+                offset: -2,
+                period: Period::Week
+            })]
+        );
+        assert_eq!(
+            parser.detect("Zeige mir die Fotos").unwrap().language().to_string(),
+            "de"
+        );
+
+        assert!(matches!(
+            parser.parse(AUTO_LANGUAGE, "42"),
+            Err(ParserError::UndetectedLanguage)
+        ));
+    }
+}
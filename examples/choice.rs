@@ -7,14 +7,13 @@ use std::time::Instant;
 async fn main() -> Result<(), anyhow::Error> {
     let text_manager = TextManager::new();
     let lang = "en";
-    let model = REMOTE_MODELS[9];
-    //let model = LOCAL_MODELS[5];
+    let model = "functiongemma";
     let is_local = false;
-    let msg = text_manager.get_msg(lang, "three-qwestions");
-    let prompt = text_manager.get_msg(lang, "which-task-for-you");
-    let preamble = text_manager.get_msg1(lang, "describe-yourself", &msg);
+    let msg = text_manager.get_msg(lang, "three-qwestions")?;
+    let prompt = text_manager.get_msg(lang, "which-task-for-you")?;
+    let preamble = text_manager.get_msg1(lang, "describe-yourself", &msg)?;
     let start = Instant::now();
-    let _ = run_agent(&preamble, &prompt, model, is_local).await?;
+    run_agent(&preamble, &prompt, model, is_local).await?;
     println!("Time elapsed: {:?}", start.elapsed());
     Ok(())
 }
@@ -32,10 +31,10 @@ async fn run_agent(
             is_local
         ));
     }
-    let client = client(is_local);
+    let client = client(is_local)?;
     let agent = client
         .agent(model)
-        .preamble(&preamble)
+        .preamble(preamble)
         .temperature(0.2)
         .build();
     let response = agent.prompt(prompt).await?;
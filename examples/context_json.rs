@@ -0,0 +1,143 @@
+use rig_test::prompt_context::{ContextParser, PromptContext};
+
+pub fn main() -> Result<(), anyhow::Error> {
+    let parser = ContextParser::new();
+    let context = parser.parse(
+        "en",
+        "Compare the last two reports since Monday, not the videos",
+    )?;
+    println!("{}", serde_json::to_string_pretty(&context)?);
+    let schema = schemars::schema_for!(PromptContext);
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use rig_test::prompt_context::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let parser = ContextParser::new();
+        for prompt in [
+            "Compare the last two reports since Monday, not the videos",
+            "Describe new images between the 1st and the 10th",
+            "Show photos from 15 December 2025 and 2025-12-02",
+            "Hello",
+        ] {
+            let context = parser.parse("en", prompt).unwrap();
+            let json = serde_json::to_string(&context).unwrap();
+            assert!(json.starts_with(r#"{"version":4,"#), "Failed for: {}", prompt);
+            let back: PromptContext = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, context, "Failed for: {}", prompt);
+        }
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let parser = ContextParser::new();
+        let context = parser.parse("en", "last 3 weeks").unwrap();
+        assert_eq!(
+            serde_json::to_value(&context).unwrap(),
+            json!({
+                "version": 4,
+                "keys": ["Last"],
+                "excluded": [],
+                "period": "Week",
+                "amount": 3,
+                "dates": [],
+                "matches": [
+                    {
+                        "term": { "Key": "Last" },
+                        "span": { "start": 0, "end": 4 },
+                        "surface": "last",
+                        "negated": false
+                    },
+                    {
+                        "term": { "Amount": 3 },
+                        "span": { "start": 5, "end": 6 },
+                        "surface": "3",
+                        "negated": false
+                    },
+                    {
+                        "term": { "Period": "Week" },
+                        "span": { "start": 7, "end": 12 },
+                        "surface": "weeks",
+                        "negated": false
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_dates_json() {
+        let between = DateConstraint::Between(
+            DateValue::Date(NaiveDate::from_ymd_opt(2025, 12, 2).unwrap()),
+            DateValue::Relative {
+                offset: -1,
+                period: Period::Day,
+            },
+        );
+        let value = serde_json::to_value(between).unwrap();
+        assert_eq!(
+            value,
+            json!({ "Between": [
+                { "Date": "2025-12-02" },
+                { "Relative": { "offset": -1, "period": "Day" } }
+            ]})
+        );
+        assert_eq!(
+            serde_json::from_value::<DateConstraint>(value).unwrap(),
+            between
+        );
+    }
+
+    #[test]
+    fn test_partial_input_and_version() {
+        let context: PromptContext =
+            serde_json::from_value(json!({ "version": 1, "keys": ["Comparison"] })).unwrap();
+        assert_eq!(context.keys(), &[PromptKey::Comparison]);
+        assert!(context.matches().is_empty());
+
+        assert_eq!(PromptContext::JSON_VERSION, 4);
+        let newer = serde_json::from_value::<PromptContext>(json!({ "version": 5 }));
+        assert!(newer.unwrap_err().to_string().contains("version 5"));
+        let older = serde_json::from_value::<PromptContext>(json!({ "version": 0 }));
+        assert!(older.is_err());
+        assert!(serde_json::from_value::<PromptContext>(json!({ "keys": [] })).is_err());
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(PromptContext)).unwrap();
+        assert_eq!(schema["title"], "PromptContext");
+        let properties = schema["properties"].as_object().unwrap();
+        for field in [
+            "version", "keys", "excluded", "period", "amount", "dates", "matches",
+        ] {
+            assert!(properties.contains_key(field), "Missing field: {}", field);
+        }
+        assert_eq!(schema["required"], json!(["version"]));
+
+        // Intent results can be handed to a model as tool arguments as well
+        let schema = serde_json::to_value(schemars::schema_for!(IntentMatch)).unwrap();
+        assert!(schema["properties"]["confidence"].is_object());
+    }
+
+    #[test]
+    fn test_custom_keys_json() {
+        let parser = ContextParser::new()
+            .with_vocabulary(Vocabulary::new().with_words("radiator", "en", ["radiator*"]));
+        let context = parser.parse("en", "Show the radiators").unwrap();
+        let value = serde_json::to_value(&context).unwrap();
+        assert_eq!(value["version"], 4);
+        assert_eq!(value["custom_keys"], json!(["radiator"]));
+        assert_eq!(value["matches"][0]["term"], json!({ "Custom": "radiator" }));
+        assert!(value.get("excluded_custom_keys").is_none());
+        let back: PromptContext = serde_json::from_value(value).unwrap();
+        assert_eq!(back, context);
+    }
+}
@@ -1,20 +1,359 @@
-/*
-use chrono::WeekdaySet;
-use date_time_parser::Recognizable;
-use event_parser::{pretty_print, to_event};
+use chrono::{Local, NaiveDate};
+use rig_test::prompt_context::{ContextParser, RangeMode};
+
 pub fn main() {
-    parse_date_events("Show my objects changes during prev two weeks");
-    parse_date_events("two weeks ago");
-    parse_date_events("last two days");
+    let parser = ContextParser::new();
+    let now = Local::now();
+    for prompt in [
+        "Show my objects changes during last two weeks",
+        "Describe new images this month",
+        "Compare all documents of the year",
+        "Compare images between the 1st and the 10th",
+        "Show photos from 15 December 2025",
+        "What changed since Monday?",
+    ] {
+        let context = match parser.parse("en", prompt) {
+            Ok(context) => context,
+            Err(e) => {
+                eprintln!("Error parsing context: {}", e);
+                continue;
+            }
+        };
+        println!("{}", prompt);
+        if !context.dates().is_empty() {
+            println!("  dates: {:?}", context.dates());
+        }
+        for mode in [RangeMode::Calendar, RangeMode::Rolling] {
+            match context.date_range(&now, mode) {
+                Some(range) => {
+                    println!("  {:?}: {} .. {}", mode, range.start(), range.end());
+                    println!("  photos: {:?}", photos_in(range));
+                }
+                None => println!("  {:?}: no dates", mode),
+            }
+        }
+    }
 }
 
-fn parse_date_events(prompt: &str) {
-    let event = to_event(prompt);
-    let date_expr: Option<WeekdaySet>> = Recognizable::recognize(prompt);
-    println!("{:#?}", date_expr);
-    pretty_print(event);
+/// Images in `data/` are named by their date: `data/2025-12-15.jpg`
+fn photos_in(range: rig_test::prompt_context::DateRange) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("data") else {
+        return vec![];
+    };
+    let mut photos: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok())
+                .is_some_and(|date| range.contains(date))
+        })
+        .map(|path| path.display().to_string())
+        .collect();
+    photos.sort();
+    photos
 }
-*/
-pub fn main() {
 
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate, TimeZone, Utc, Weekday};
+    use rig_test::prompt_context::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn range(prompt: &str, mode: RangeMode) -> Option<DateRange> {
+        let parser = ContextParser::new();
+        // Wednesday
+        let now = Utc.with_ymd_and_hms(2025, 12, 17, 12, 0, 0).unwrap();
+        parser.parse("en", prompt).unwrap().date_range(&now, mode)
+    }
+
+    #[test]
+    fn test_last_weeks_calendar() {
+        let result = range("Detect changes during last two weeks", RangeMode::Calendar).unwrap();
+        assert_eq!(
+            result,
+            DateRange::new(date(2025, 12, 1), date(2025, 12, 14))
+        );
+    }
+
+    #[test]
+    fn test_last_weeks_rolling() {
+        let result = range("Detect changes during last two weeks", RangeMode::Rolling).unwrap();
+        assert_eq!(
+            result,
+            DateRange::new(date(2025, 12, 4), date(2025, 12, 17))
+        );
+    }
+
+    #[test]
+    fn test_new_is_current_period_to_date() {
+        let result = range("new reports this month", RangeMode::Calendar).unwrap();
+        assert_eq!(
+            result,
+            DateRange::new(date(2025, 12, 1), date(2025, 12, 17))
+        );
+    }
+
+    #[test]
+    fn test_all_covers_whole_period() {
+        let result = range("all reports of the week", RangeMode::Calendar).unwrap();
+        assert_eq!(
+            result,
+            DateRange::new(date(2025, 12, 15), date(2025, 12, 21))
+        );
+    }
+
+    #[test]
+    fn test_last_quarter_and_year() {
+        let quarter = range("last quarter", RangeMode::Calendar).unwrap();
+        assert_eq!(quarter, DateRange::new(date(2025, 7, 1), date(2025, 9, 30)));
+        let year = range("previous year", RangeMode::Calendar).unwrap();
+        assert_eq!(year, DateRange::new(date(2024, 1, 1), date(2024, 12, 31)));
+    }
+
+    #[test]
+    fn test_no_period() {
+        assert_eq!(range("Show all reports", RangeMode::Calendar), None);
+    }
+
+    #[test]
+    fn test_explicit_dates_en() {
+        let parser = ContextParser::new();
+        let cases = vec![
+            (
+                "Show images from 2025-12-02",
+                DateConstraint::On(DateValue::Date(date(2025, 12, 2))),
+            ),
+            (
+                "on 15.12.2025.",
+                DateConstraint::On(DateValue::Date(date(2025, 12, 15))),
+            ),
+            (
+                "photos of 15 December",
+                DateConstraint::On(DateValue::DayMonth { day: 15, month: 12 }),
+            ),
+            (
+                "December 5th, 2025",
+                DateConstraint::On(DateValue::Date(date(2025, 12, 5))),
+            ),
+        ];
+        for (prompt, expected) in cases {
+            assert_eq!(
+                parser.parse_dates("en", prompt).unwrap(),
+                vec![expected],
+                "Failed for: {}",
+                prompt
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_dates_en() {
+        let parser = ContextParser::new();
+        let two_weeks_ago = DateValue::Relative {
+            offset: -2,
+            period: Period::Week,
+        };
+        let yesterday = DateValue::Relative {
+            offset: -1,
+            period: Period::Day,
+        };
+        let cases = vec![
+            (
+                "Changes two weeks ago",
+                vec![DateConstraint::On(two_weeks_ago)],
+            ),
+            (
+                "a month ago",
+                vec![DateConstraint::On(DateValue::Relative {
+                    offset: -1,
+                    period: Period::Month,
+                })],
+            ),
+            (
+                "since Monday",
+                vec![DateConstraint::Since(DateValue::Weekday(Weekday::Mon))],
+            ),
+            ("Describe yesterday", vec![DateConstraint::On(yesterday)]),
+            (
+                "between the 1st and the 10th",
+                vec![DateConstraint::Between(
+                    DateValue::DayOfMonth(1),
+                    DateValue::DayOfMonth(10),
+                )],
+            ),
+            (
+                "from Monday to Friday",
+                vec![DateConstraint::Between(
+                    DateValue::Weekday(Weekday::Mon),
+                    DateValue::Weekday(Weekday::Fri),
+                )],
+            ),
+            ("Show 5 reports for the last day", vec![]),
+        ];
+        for (prompt, expected) in cases {
+            assert_eq!(
+                parser.parse_dates("en", prompt).unwrap(),
+                expected,
+                "Failed for: {}",
+                prompt
+            );
+        }
+    }
+
+    #[test]
+    fn test_dates_de() {
+        let parser = ContextParser::new();
+        let cases = vec![
+            (
+                "Fotos vom 15. Dezember",
+                DateConstraint::On(DateValue::DayMonth { day: 15, month: 12 }),
+            ),
+            (
+                "am 02.12.2025",
+                DateConstraint::On(DateValue::Date(date(2025, 12, 2))),
+            ),
+            (
+                "Bilder vom 15.12.",
+                DateConstraint::On(DateValue::DayMonth { day: 15, month: 12 }),
+            ),
+            (
+                "Änderungen vor zwei Wochen",
+                DateConstraint::On(DateValue::Relative {
+                    offset: -2,
+                    period: Period::Week,
+                }),
+            ),
+            (
+                "vor einem Monat",
+                DateConstraint::On(DateValue::Relative {
+                    offset: -1,
+                    period: Period::Month,
+                }),
+            ),
+            (
+                "seit Montag",
+                DateConstraint::Since(DateValue::Weekday(Weekday::Mon)),
+            ),
+            (
+                "gestern",
+                DateConstraint::On(DateValue::Relative {
+                    offset: -1,
+                    period: Period::Day,
+                }),
+            ),
+            (
+                "zwischen dem 1. und dem 10.",
+                DateConstraint::Between(DateValue::DayOfMonth(1), DateValue::DayOfMonth(10)),
+            ),
+        ];
+        for (prompt, expected) in cases {
+            assert_eq!(
+                parser.parse_dates("de", prompt).unwrap(),
+                vec![expected],
+                "Failed for: {}",
+                prompt
+            );
+        }
+    }
+
+    #[test]
+    fn test_dates_resolve_to_range() {
+        assert_eq!(
+            range(
+                "Compare images between the 1st and the 10th",
+                RangeMode::Calendar
+            ),
+            Some(DateRange::new(date(2025, 12, 1), date(2025, 12, 10)))
+        );
+        assert_eq!(
+            range("What changed since Monday?", RangeMode::Calendar),
+            Some(DateRange::new(date(2025, 12, 15), date(2025, 12, 17)))
+        );
+        // Explicit dates win over the period
+        assert_eq!(
+            range(
+                "Show the report of 15 December, not the last week",
+                RangeMode::Rolling
+            ),
+            Some(DateRange::new(date(2025, 12, 15), date(2025, 12, 15)))
+        );
+        // Early in the month the 10th is still ahead, so the previous month is meant
+        let today = date(2025, 12, 5);
+        let between = DateConstraint::Between(DateValue::DayOfMonth(1), DateValue::DayOfMonth(10));
+        assert_eq!(
+            between.resolve(today),
+            Some(DateRange::new(date(2025, 11, 1), date(2025, 11, 10)))
+        );
+    }
+
+    #[test]
+    fn test_ordinals_and_years() {
+        let parser = ContextParser::new();
+        // An ordinal without a date word counts things
+        let context = parser.parse("en", "Show me the 3rd photo").unwrap();
+        assert!(context.dates().is_empty());
+        assert_eq!(context.amount(), Some(3));
+        let context = parser.parse("de", "Zeige das 3. Foto").unwrap();
+        assert!(context.dates().is_empty());
+        assert_eq!(context.amount(), Some(3));
+
+        let cases = vec![
+            ("on the 3rd", DateConstraint::On(DateValue::DayOfMonth(3))),
+            (
+                "the 3rd of December",
+                DateConstraint::On(DateValue::DayMonth { day: 3, month: 12 }),
+            ),
+            ("in 1999", DateConstraint::On(DateValue::Year(1999))),
+            ("since 2020", DateConstraint::Since(DateValue::Year(2020))),
+            (
+                "between 1999 and 2003",
+                DateConstraint::Between(DateValue::Year(1999), DateValue::Year(2003)),
+            ),
+        ];
+        for (prompt, expected) in cases {
+            assert_eq!(
+                parser.parse_dates("en", prompt).unwrap(),
+                vec![expected],
+                "Failed for: {}",
+                prompt
+            );
+        }
+
+        // A year after a date word is no amount
+        let context = parser.parse("en", "Show reports from 1999").unwrap();
+        assert_eq!(context.dates(), &[DateConstraint::On(DateValue::Year(1999))]);
+        assert_eq!(context.amount(), None);
+        assert_eq!(
+            context.dates()[0].resolve(date(2025, 12, 17)),
+            Some(DateRange::new(date(1999, 1, 1), date(1999, 12, 31)))
+        );
+        let context = parser.parse("en", "Show 2000 photos").unwrap();
+        assert!(context.dates().is_empty());
+        assert_eq!(context.amount(), Some(2000));
+        assert_eq!(
+            parser.parse_dates("de", "Berichte seit 2020").unwrap(),
+            vec![DateConstraint::Since(DateValue::Year(2020))]
+        );
+    }
+
+    #[test]
+    fn test_timezone_changes_today() {
+        let parser = ContextParser::new();
+        let context = parser.parse("en", "new images this day").unwrap();
+        // Late evening in UTC is already the next day in UTC+3
+        let utc = Utc.with_ymd_and_hms(2025, 12, 14, 22, 30, 0).unwrap();
+        let moscow = utc.with_timezone(&FixedOffset::east_opt(3 * 3600).unwrap());
+
+        let result = context.date_range(&utc, RangeMode::Calendar).unwrap();
+        assert_eq!(result.start(), date(2025, 12, 14));
+        let result = context.date_range(&moscow, RangeMode::Calendar).unwrap();
+        assert_eq!(result.start(), date(2025, 12, 15));
+        assert!(result.contains(date(2025, 12, 15)));
+        assert!(!result.contains(date(2025, 12, 14)));
+    }
+}
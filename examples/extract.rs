@@ -1,35 +1,8 @@
 use rig::client::CompletionClient;
-use rig_test::helper::{LOCAL_MODELS, REMOTE_MODELS, client};
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use rig_test::helper::{Capability, ModelRegistry, client};
+use rig_test::prompt_context::{ExtractedEntities, HybridExtractor};
 use std::time::Instant;
 
-#[derive(Debug, Deserialize, JsonSchema, Serialize)]
-enum EntityType {
-    Object,
-    Document,
-    Description,
-    Comparison,
-    Period,
-    Amount,
-    Date,
-    Other(String),
-}
-
-#[derive(Debug, Deserialize, JsonSchema, Serialize)]
-struct Entity {
-    entity_type: EntityType,
-    name: String,
-    confidence: f32,
-}
-
-#[derive(Debug, Deserialize, JsonSchema, Serialize)]
-struct ExtractedEntities {
-    entities: Vec<Entity>,
-    total_count: usize,
-    extraction_time: String, // ISO 8601 formatted string
-}
-
 fn pretty_print_entities(extracted: &ExtractedEntities) {
     println!("Extracted Entities:");
     println!("Total Count: {}", extracted.total_count);
@@ -46,10 +19,10 @@ fn pretty_print_entities(extracted: &ExtractedEntities) {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let is_local = false;
-    let client = client(is_local);
-    let n = 1;
-    let model = REMOTE_MODELS[n];
-    //let model = LOCAL_MODELS[n];
+    let client = client(is_local)?;
+    // The extractor calls a tool to return the entities
+    let model = "qwen3-vl";
+    ModelRegistry::builtin().require(model, is_local.into(), &[Capability::Tools])?;
     // 0: Entities: qwen3-vl:235b-cloud
     //   - Type: Period, Name: last, Confidence: 0.95
     //   - Type: Description, Name: changes, Confidence: 0.98
@@ -77,7 +50,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Error extracting entities: Failed to deserialize the extracted data: invalid type: string "[{\"confidence\": 0.8, \"entity_type\": \"Period\", \"name\": \"two weeks\"}]", expected a sequence
     // Time elapsed: 65.3428555s
     // 3 - error
-    // does not support tools - 2,4,5,6,7,8, 11
     // Create the extractor
     let extractor = client
         .extractor::<ExtractedEntities>(model)
@@ -100,8 +72,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     //let sample_text = "Show objects with changes during 5 days";
 
     println!(
-        "{:?}: Extracting entities from the following text:\n{}\n",
-        n, sample_text
+        "{}: Extracting entities from the following text:\n{}\n",
+        model, sample_text
     );
     let start = Instant::now();
     // Extract entities
@@ -112,5 +84,171 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Err(e) => eprintln!("Error extracting entities: {}", e),
     }
     println!("Time elapsed: {:?}", start.elapsed());
+
+    // The rules answer this one on their own, the LLM is asked only for vague prompts
+    let hybrid = HybridExtractor::new(extractor);
+    for prompt in [sample_text, "Anything new on the site?"] {
+        let start = Instant::now();
+        let result = hybrid.extract("en", prompt).await?;
+        println!(
+            "{}: {:?} ({:.2}), keys: {:?}, llm: {}, time: {:?}",
+            prompt,
+            result.intent().intent(),
+            result.intent().confidence(),
+            result.context().keys(),
+            result.llm_used(),
+            start.elapsed()
+        );
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use rig::extractor::ExtractionError;
+    use rig_test::prompt_context::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers with canned entities and counts the calls
+    struct FakeExtractor {
+        entities: Option<Vec<Entity>>,
+        calls: AtomicUsize,
+    }
+
+    impl FakeExtractor {
+        fn new(entities: Option<Vec<(EntityType, &str, f32)>>) -> Self {
+            Self {
+                entities: entities.map(|entities| {
+                    entities
+                        .into_iter()
+                        .map(|(entity_type, name, confidence)| Entity {
+                            entity_type,
+                            name: name.to_string(),
+                            confidence,
+                        })
+                        .collect()
+                }),
+                calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl EntityExtractor for FakeExtractor {
+        async fn extract_entities(
+            &self,
+            _prompt: &str,
+        ) -> Result<ExtractedEntities, ExtractionError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let entities = self.entities.clone().ok_or(ExtractionError::NoData)?;
+            Ok(ExtractedEntities {
+                total_count: entities.len(),
+                entities,
+                extraction_time: "2025-12-17T12:00:00Z".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rules_suffice() {
+        let hybrid = HybridExtractor::new(FakeExtractor::new(Some(vec![])));
+        let result = hybrid
+            .extract("en", "Detect changes during last two weeks")
+            .await
+            .unwrap();
+        assert!(!result.llm_used());
+        assert_eq!(hybrid.extractor().calls.load(Ordering::SeqCst), 0);
+        assert_eq!(result.intent().intent(), Intent::CompareLatest);
+        assert_eq!(result.context().period(), Some(Period::Week));
+        let provenance = result.key_provenance(PromptKey::Comparison).unwrap();
+        assert_eq!(provenance.source(), Source::Rules);
+        assert_eq!(provenance.confidence(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_llm_fills_gaps() {
+        let extractor = FakeExtractor::new(Some(vec![
+            (EntityType::Comparison, "anything new", 0.8),
+            (EntityType::Period, "two weeks", 0.7),
+            (EntityType::Other("site".to_string()), "site", 0.9),
+        ]));
+        let hybrid = HybridExtractor::new(extractor);
+        let result = hybrid
+            .extract("en", "Anything new on the site?")
+            .await
+            .unwrap();
+        assert!(result.llm_used());
+        assert_eq!(result.llm_error(), None);
+
+        let context = result.context();
+        assert_eq!(context.keys(), &[PromptKey::New, PromptKey::Comparison]);
+        assert_eq!(context.period(), Some(Period::Week));
+        assert_eq!(context.amount(), Some(2));
+        assert_eq!(result.intent().intent(), Intent::CompareLatest);
+
+        assert_eq!(
+            result.key_provenance(PromptKey::New).unwrap().source(),
+            Source::Rules
+        );
+        let comparison = result.key_provenance(PromptKey::Comparison).unwrap();
+        assert_eq!(comparison.source(), Source::Llm);
+        assert_eq!(comparison.confidence(), 0.8);
+        assert_eq!(result.period_provenance().unwrap().source(), Source::Llm);
+        assert_eq!(result.key_provenance(PromptKey::Object), None);
+    }
+
+    #[tokio::test]
+    async fn test_rules_win_and_agree() {
+        let extractor = FakeExtractor::new(Some(vec![
+            (EntityType::Description, "describe", 0.9),
+            (EntityType::Comparison, "compare", 0.6),
+            (EntityType::Period, "month", 0.9),
+        ]));
+        // A rule table that never matches forces the LLM
+        let resolver = IntentResolver::from_rules(vec![]).unwrap();
+        let hybrid = HybridExtractor::new(extractor).with_resolver(resolver);
+        let result = hybrid
+            .extract("en", "Describe the images of last week, don't compare")
+            .await
+            .unwrap();
+        assert!(result.llm_used());
+        assert_eq!(
+            result
+                .key_provenance(PromptKey::Description)
+                .unwrap()
+                .source(),
+            Source::Both
+        );
+        // Negated by the rules, the LLM cannot bring it back
+        assert!(!result.context().has_key(PromptKey::Comparison));
+        assert_eq!(result.context().period(), Some(Period::Week));
+        assert_eq!(result.period_provenance().unwrap().source(), Source::Rules);
+    }
+
+    #[tokio::test]
+    async fn test_llm_error_keeps_rules() {
+        let hybrid = HybridExtractor::new(FakeExtractor::new(None));
+        let result = hybrid.extract("en", "Show me the latest").await.unwrap();
+        assert!(result.llm_used());
+        assert!(result.llm_error().is_some());
+        assert_eq!(result.context().keys(), &[PromptKey::New]);
+        assert_eq!(hybrid.extractor().calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auto_language_entities() {
+        // "2" alone has no language, the prompt's one is used
+        let extractor = FakeExtractor::new(Some(vec![
+            (EntityType::Comparison, "anything new", 0.8),
+            (EntityType::Amount, "2", 0.9),
+        ]));
+        let hybrid = HybridExtractor::new(extractor);
+        let result = hybrid
+            .extract(AUTO_LANGUAGE, "Is there anything new on the site since the last visit?")
+            .await
+            .unwrap();
+        assert!(result.llm_used());
+        assert_eq!(result.context().amount(), Some(2));
+        assert_eq!(result.amount_provenance().unwrap().source(), Source::Llm);
+        assert!(result.context().has_key(PromptKey::Comparison));
+    }
+}
@@ -8,8 +8,9 @@ use std::time::Instant;
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     let is_local = false;
-    let client = client(is_local);
-    let tool_model = "functiongemma"; //REMOTE_MODELS[9]; // functiongemma
+    let client = client(is_local)?;
+    let tool_model = "functiongemma";
+    ModelRegistry::builtin().require(tool_model, is_local.into(), &[Capability::Tools])?;
     let tool_agent = client
         .agent(tool_model)
         .preamble("You are a model that can do function calling with the following functions")
@@ -16,8 +16,14 @@ use tokio::fs;
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
     let is_local = false;
-    let model = REMOTE_MODELS[10];
-    //let model = LOCAL_MODELS[6];
+    // Qwen3-VL-4B, the model this example was tuned with
+    let model = &ModelRegistry::builtin()
+        .require(
+            "adelnazmy2002/Qwen3-VL-4B-Instruct:Q8_0",
+            is_local.into(),
+            &[Capability::Vision],
+        )?
+        .name;
     //let img: &str = "D:/projects/rust/cx/cx58-agent/data/3w_5.jpg";
     //let img: &str = "./data/4к_1.jpg";
     let img: &str = "./data/4k_4.jpg";
@@ -43,20 +49,16 @@ async fn describe(
     let json = serde_json::json!({
         "format": "json"
     });
-    if !check_model(model, is_local) {
-        return Err(anyhow::anyhow!(
-            "Model not found: {}, is_local: {}",
-            model,
-            is_local
-        ));
-    }
-    let client = client(is_local);
+    // Fails early when the model was removed from the server
+    check_model_available(model, is_local, &[Capability::Vision]).await?;
+    let client = client(is_local)?;
 
     //let language = "English";
     //let language = "German";
     // Translate responce to: {}
     // Translate responce to German.
 
+    #[allow(clippy::useless_format)]
     let system = format!(
         r#"
 You are a precise, reliable, and concise assistant.
@@ -0,0 +1,154 @@
+use rig_test::prompt_context::{ContextParser, IntentResolver};
+
+pub fn main() {
+    let parser = ContextParser::new();
+    let resolver = IntentResolver::new();
+    for prompt in [
+        "Describe the latest image",
+        "Compare images between the 1st and the 10th",
+        "What changed during the last two weeks?",
+        "Show all documents",
+        "Create a report",
+        "Hello, how are you?",
+    ] {
+        match parser.parse("en", prompt) {
+            Ok(context) => {
+                let intent = resolver.resolve(&context);
+                println!(
+                    "{:<50} {:?} ({:.2})",
+                    prompt,
+                    intent.intent(),
+                    intent.confidence()
+                );
+            }
+            Err(e) => eprintln!("Error parsing context: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rig_test::prompt_context::*;
+
+    /// Labelled prompts, the resolver must pick the intent for each
+    const CORPUS: &[(&str, Intent)] = &[
+        ("Describe the latest image", Intent::DescribeLatest),
+        ("Describe the newest pictures", Intent::DescribeLatest),
+        ("Please describe the recent video", Intent::DescribeLatest),
+        ("Describe this picture", Intent::DescribeLatest),
+        ("Describe the photo of 15 December", Intent::DescribeDate),
+        ("Describe what happened yesterday", Intent::DescribeDate),
+        (
+            "Compare images between the 1st and the 10th",
+            Intent::CompareTwoDates,
+        ),
+        (
+            "Compare 2025-12-02 with 2025-12-15",
+            Intent::CompareTwoDates,
+        ),
+        ("What changed since Monday?", Intent::CompareTwoDates),
+        (
+            "What changed during the last two weeks?",
+            Intent::CompareLatest,
+        ),
+        ("Compare the latest images", Intent::CompareLatest),
+        ("Detect differences in the object", Intent::CompareLatest),
+        ("Show all documents", Intent::ListDocuments),
+        ("List the last 5 reports", Intent::ListDocuments),
+        ("Which files do I have?", Intent::ListDocuments),
+        ("Create a report", Intent::CreateReport),
+        ("Make a document of the pictures", Intent::CreateReport),
+        ("Hello, how are you?", Intent::Nothing),
+        ("Tell me a joke", Intent::Nothing),
+        ("Don't compare anything", Intent::Nothing),
+    ];
+
+    #[test]
+    fn test_corpus() {
+        let parser = ContextParser::new();
+        let resolver = IntentResolver::new();
+        for (prompt, expected) in CORPUS {
+            let context = parser.parse("en", prompt).unwrap();
+            assert_eq!(
+                resolver.resolve(&context).intent(),
+                *expected,
+                "Failed for: {}",
+                prompt
+            );
+        }
+    }
+
+    #[test]
+    fn test_confidence() {
+        let parser = ContextParser::new();
+        let resolver = IntentResolver::new();
+
+        let small_talk = resolver.resolve(&parser.parse("en", "Hello there").unwrap());
+        assert_eq!(small_talk.intent(), Intent::Nothing);
+        assert_eq!(small_talk.confidence(), 1.0);
+        assert_eq!(small_talk.rule(), None);
+
+        // More specific rules are more confident
+        let latest = resolver.resolve(&parser.parse("en", "Describe the latest").unwrap());
+        let plain = resolver.resolve(&parser.parse("en", "Describe it").unwrap());
+        assert_eq!(latest.intent(), plain.intent());
+        assert!(latest.confidence() > plain.confidence());
+
+        // Keys no rule explains lower the confidence
+        let extra = resolver.resolve(&parser.parse("en", "Describe the latest building").unwrap());
+        assert!(extra.confidence() < latest.confidence());
+    }
+
+    #[test]
+    fn test_candidates() {
+        let parser = ContextParser::new();
+        let resolver = IntentResolver::new();
+        let context = parser
+            .parse("en", "Describe and compare the images of yesterday")
+            .unwrap();
+        let intents: Vec<Intent> = resolver
+            .candidates(&context)
+            .iter()
+            .map(|candidate| candidate.intent())
+            .collect();
+        assert!(intents.contains(&Intent::DescribeDate));
+        assert!(intents.contains(&Intent::CompareTwoDates));
+        assert!(!intents.contains(&Intent::ListDocuments));
+    }
+
+    #[test]
+    fn test_rules_from_file() {
+        let path = std::env::temp_dir().join("rig_test_intents.json");
+        std::fs::write(
+            &path,
+            r#"[{ "intent": "CreateReport", "all": ["Document"], "confidence": 0.6 }]"#,
+        )
+        .unwrap();
+        let resolver = IntentResolver::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resolver.rules().len(), 1);
+
+        let context = ContextParser::new()
+            .parse("en", "Show all documents")
+            .unwrap();
+        let intent = resolver.resolve(&context);
+        assert_eq!(intent.intent(), Intent::CreateReport);
+        assert_eq!(intent.rule(), Some(0));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(matches!(
+            IntentResolver::from_json(r#"[{ "intent": "Nothing", "confidence": 1.5 }]"#),
+            Err(IntentError::InvalidConfidence { index: 0, .. })
+        ));
+        assert!(matches!(
+            IntentResolver::from_json(r#"[{ "intent": "Dance", "confidence": 0.5 }]"#),
+            Err(IntentError::Json(_))
+        ));
+        assert!(matches!(
+            IntentResolver::from_file("missing/intents.json"),
+            Err(IntentError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,445 @@
+use rig_test::lang::{Fallback, TextManager};
+
+fn main() {
+    let text_manager = TextManager::new();
+    for lang in ["en", "de", "ru"] {
+        match text_manager.get_msg(lang, "which-task-for-you") {
+            Ok(msg) => println!("{}: {}", lang, msg),
+            Err(e) => eprintln!("{}: {}", lang, e),
+        }
+    }
+
+    let strict = TextManager::new().with_fallback(Fallback::Strict);
+    if let Err(e) = strict.get_msg("ru", "which-task-for-you") {
+        eprintln!("strict: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rig_test::lang::*;
+
+    #[test]
+    fn test_get_msg_with_param() {
+        let text_manager = TextManager::new();
+        let msg = text_manager.get_msg1("en", "describe-yourself", "Hi").unwrap();
+
+        assert!(msg.contains("User message: \u{2068}Hi\u{2069}"));
+    }
+
+    #[test]
+    fn test_missing_message() {
+        let text_manager = TextManager::new();
+        let result = text_manager.get_msg("en", "no-such-message");
+
+        assert_eq!(
+            result,
+            Err(TextError::MissingMessage {
+                lang: "en".to_string(),
+                id: "no-such-message".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_default_fallback_is_english() {
+        let text_manager = TextManager::new();
+
+        assert_eq!(text_manager.fallback(), &Fallback::Language("en".to_string()));
+        assert_eq!(
+            text_manager.get_msg("ru", "which-task-for-you"),
+            text_manager.get_msg("en", "which-task-for-you")
+        );
+        // Missing in `de` and `en` alike
+        assert!(matches!(
+            text_manager.split_msg("de", "no-such-words"),
+            Err(TextError::MissingMessage { .. })
+        ));
+        assert!(text_manager.has_msg("de", "object-words"));
+    }
+
+    #[test]
+    fn test_strict_fallback() {
+        let text_manager = TextManager::new().with_fallback(Fallback::Strict);
+
+        assert_eq!(
+            text_manager.get_msg("ru", "which-task-for-you"),
+            Err(TextError::UnsupportedLanguage("ru".to_string()))
+        );
+        assert!(matches!(
+            text_manager.split_msg("de", "no-such-words"),
+            Err(TextError::MissingMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let text_manager = TextManager::new();
+        let detector = LanguageDetector::new(&text_manager);
+        let cases = [
+            ("Compare the images of the last two weeks", "en"),
+            ("Show me what changed since Monday", "en"),
+            ("Vergleiche die Bilder der letzten zwei Wochen", "de"),
+            ("Zeige mir die Fotos vom 15. Dezember", "de"),
+            ("Was hat sich seit Montag geändert?", "de"),
+        ];
+        for (text, expected) in cases {
+            let detection = detector.detect(text).unwrap();
+            assert_eq!(detection.language().to_string(), expected, "Failed for: {}", text);
+            assert!(detection.confidence() > 0.5, "Failed for: {}", text);
+        }
+        assert_eq!(
+            text_manager.detect("Wie geht es dir?").unwrap().language(),
+            &"de".parse::<unic_langid::LanguageIdentifier>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detect_scores() {
+        let detector = LanguageDetector::new(&TextManager::new());
+        let scores = detector.scores("the images");
+        assert_eq!(scores.len(), 2);
+        assert!(scores[0].confidence() >= scores[1].confidence());
+        let total: f32 = scores.iter().map(|s| s.confidence()).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+
+        assert_eq!(detector.detect("1234 ?!"), None);
+        assert_eq!(detector.detect(""), None);
+    }
+
+    #[test]
+    fn test_detect_ambiguous() {
+        let detector = LanguageDetector::new(&TextManager::new());
+        // Short words both locales could have, the scores still rank them
+        for text in ["hello", "Hallo", "super"] {
+            assert_eq!(detector.detect(text), None, "Failed for: {}", text);
+            assert_eq!(detector.scores(text).len(), 2, "Failed for: {}", text);
+        }
+        // No known word, a few shared trigrams are no evidence
+        for text in ["ok", "hi", "2 photos"] {
+            let scores = detector.scores(text);
+            assert!(scores[0].score() < 0.1, "Failed for: {}", text);
+            assert_eq!(detector.detect(text), None, "Failed for: {}", text);
+        }
+        let detection = detector.detect("Show me the photos").unwrap();
+        assert_eq!(detection.language().to_string(), "en");
+        assert!(detection.margin() >= 0.2);
+
+        let scores = detector.scores("hello");
+        assert!((scores[0].margin() + scores[1].margin()).abs() < 1e-5);
+        let lenient = LanguageDetector::new(&TextManager::new())
+            .with_min_score(0.0)
+            .with_min_margin(0.0);
+        assert!(lenient.detect("hello").is_some());
+        assert!(lenient.detect("ok").is_some());
+    }
+
+    #[test]
+    fn test_languages_and_ids() {
+        let text_manager = TextManager::new();
+        assert_eq!(text_manager.languages(), vec!["de", "en"]);
+        assert_eq!(text_manager.message_ids("en")[0], "describe-yourself");
+        assert!(text_manager.message_ids("ru").is_empty());
+    }
+
+    #[test]
+    fn test_lint_embedded() {
+        let report = rig_test::lang::lint_embedded();
+        assert!(report.is_ok(), "{:#?}", report.errors);
+        assert_eq!(report.languages, vec!["de", "en"]);
+    }
+
+    #[test]
+    fn test_lint_issues() {
+        use rig_test::lang::{LintIssue, lint_locales};
+
+        let en = "greet = Hello { $p1 }\nbye = Bye\n-brand = Rig\n";
+        // Like the bundle, the lint keeps the last definition
+        let de = "greet = Servus\ngreet = Hallo { $p2 }\nextra = Mehr\n-brand = Rig\nbroken = { $p1 ]\n";
+        let report = lint_locales(&[("en", en), ("de", de)], "en");
+
+        assert!(!report.is_ok());
+        assert!(report.errors.contains(&LintIssue::MissingMessage {
+            lang: "de".to_string(),
+            id: "bye".to_string()
+        }));
+        assert!(report.errors.contains(&LintIssue::DuplicateMessage {
+            lang: "de".to_string(),
+            id: "greet".to_string()
+        }));
+        assert!(report.errors.contains(&LintIssue::MismatchedVariables {
+            lang: "de".to_string(),
+            id: "greet".to_string(),
+            expected: vec!["$p1".to_string()],
+            found: vec!["$p2".to_string()],
+        }));
+        assert!(report.errors.iter().any(|issue| matches!(
+            issue,
+            LintIssue::SyntaxError { lang, line: 5, .. } if lang == "de"
+        )));
+        assert_eq!(
+            report.warnings,
+            vec![LintIssue::ExtraMessage {
+                lang: "de".to_string(),
+                id: "extra".to_string()
+            }]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["reference"], "en");
+        assert_eq!(json["warnings"][0]["kind"], "extra-message");
+        assert_eq!(json["warnings"][0]["id"], "extra");
+    }
+
+    #[test]
+    fn test_lint_dir() {
+        use rig_test::lang::{LintIssue, lint_dir};
+
+        let dir = locale_dir("lint_dir");
+        std::fs::write(dir.join("en.ftl"), "greet = Hello { $p1 }\nbye = Bye\n").unwrap();
+        std::fs::create_dir(dir.join("fr")).unwrap();
+        std::fs::write(dir.join("fr/a.ftl"), "greet = Bonjour { $p1 }\n").unwrap();
+        std::fs::write(dir.join("fr/b.ftl"), "greet = Salut { $p1 }\n").unwrap();
+        let report = lint_dir(&dir, "en").unwrap();
+        assert_eq!(report.languages, vec!["en", "fr"]);
+        // The files of a language are linted together
+        assert_eq!(
+            report.errors,
+            vec![
+                LintIssue::DuplicateMessage {
+                    lang: "fr".to_string(),
+                    id: "greet".to_string()
+                },
+                LintIssue::MissingMessage {
+                    lang: "fr".to_string(),
+                    id: "bye".to_string()
+                },
+            ]
+        );
+
+        std::fs::write(dir.join("fr/b.ftl"), "bye = Au revoir\n").unwrap();
+        assert!(lint_dir(&dir, "en").unwrap().is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(lint_dir(&dir, "en").is_err());
+    }
+
+    #[test]
+    fn test_lint_parents() {
+        use rig_test::lang::{LintIssue, lint_dir, lint_locales};
+
+        let en = "greet = Hello\nbye = Bye\n";
+        let de_ch = "greet = Grüezi\n";
+        let missing = |lang: &str| LintIssue::MissingMessage {
+            lang: lang.to_string(),
+            id: "bye".to_string(),
+        };
+        // A regional language only overrides its parent
+        let report = lint_locales(&[("en", en), ("de", en), ("de-CH", de_ch)], "en");
+        assert!(report.is_ok(), "{:#?}", report.errors);
+        let report = lint_locales(&[("en", en), ("de", de_ch), ("de-CH", de_ch)], "en");
+        assert_eq!(report.errors, vec![missing("de"), missing("de-CH")]);
+
+        // Without the reference nothing is compared, which is an error
+        let dir = locale_dir("lint_parents");
+        std::fs::write(dir.join("de-CH.ftl"), de_ch).unwrap();
+        let report = lint_dir(&dir, "en").unwrap();
+        assert_eq!(
+            report.errors,
+            vec![LintIssue::MissingReference {
+                lang: "en".to_string()
+            }]
+        );
+        assert!(!report.is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// An empty directory under the system temp dir, unique per test
+    fn locale_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("lang_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir = locale_dir("from_dir");
+        std::fs::write(dir.join("en.ftl"), "which-task-for-you = What now?\n").unwrap();
+        std::fs::create_dir(dir.join("fr")).unwrap();
+        std::fs::write(dir.join("fr/a.ftl"), "greet = Bonjour { $p1 }\n").unwrap();
+        std::fs::write(dir.join("fr/b.ftl"), "bye = Au revoir\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let text_manager = TextManager::from_dir(&dir).unwrap();
+        assert_eq!(text_manager.languages(), vec!["de", "en", "fr"]);
+        // The directory overrides, the embedded messages stay as fallback
+        assert_eq!(text_manager.get_msg("en", "which-task-for-you").unwrap(), "What now?");
+        assert!(text_manager.has_msg("en", "object-words"));
+        assert_eq!(
+            text_manager.get_msg1("fr", "greet", "Anna").unwrap(),
+            "Bonjour \u{2068}Anna\u{2069}"
+        );
+        assert_eq!(text_manager.get_msg("fr", "bye").unwrap(), "Au revoir");
+        assert_eq!(text_manager.message_ids("fr"), vec!["greet", "bye"]);
+
+        assert!(matches!(
+            TextManager::from_dir(dir.join("missing")),
+            Err(TextError::Io { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload() {
+        let dir = locale_dir("reload");
+        std::fs::write(dir.join("fr.ftl"), "greet = Bonjour\n").unwrap();
+        let text_manager = TextManager::from_dir(&dir).unwrap();
+
+        std::fs::write(dir.join("fr.ftl"), "greet = Salut\n").unwrap();
+        assert_eq!(text_manager.get_msg("fr", "greet").unwrap(), "Bonjour");
+        text_manager.reload().unwrap();
+        assert_eq!(text_manager.get_msg("fr", "greet").unwrap(), "Salut");
+
+        // A broken file keeps the previous bundles
+        std::fs::write(dir.join("fr.ftl"), "greet = { Salut\n").unwrap();
+        assert!(matches!(
+            text_manager.reload(),
+            Err(TextError::InvalidResource { .. })
+        ));
+        assert_eq!(text_manager.get_msg("fr", "greet").unwrap(), "Salut");
+
+        // Without a directory there is nothing to reload or watch
+        assert_eq!(TextManager::new().reload(), Ok(()));
+        assert!(matches!(
+            std::sync::Arc::new(TextManager::new()).watch(),
+            Err(TextError::Watch(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_parser() {
+        use rig_test::prompt_context::{ContextParser, PromptKey};
+
+        let dir = locale_dir("reload_parser");
+        std::fs::write(dir.join("en.ftl"), "comparison-words = compar*\n").unwrap();
+        let text_manager = std::sync::Arc::new(TextManager::from_dir(&dir).unwrap());
+        let parser = ContextParser::new().with_text_manager(text_manager.clone());
+        let contrast = || parser.parse("en", "Contrast the reports").unwrap();
+        assert!(!contrast().has_key(PromptKey::Comparison));
+
+        // The cached word lists are rebuilt from the reloaded messages
+        std::fs::write(dir.join("en.ftl"), "comparison-words = compar* contrast*\n").unwrap();
+        text_manager.reload().unwrap();
+        assert_eq!(text_manager.generation(), 1);
+        assert!(contrast().has_key(PromptKey::Comparison));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_watch() {
+        let dir = locale_dir("watch");
+        std::fs::write(dir.join("fr.ftl"), "greet = Bonjour\n").unwrap();
+        let text_manager = std::sync::Arc::new(TextManager::from_dir(&dir).unwrap());
+        let _watcher = text_manager.watch().unwrap();
+
+        std::fs::write(dir.join("fr.ftl"), "greet = Salut\n").unwrap();
+        let reloaded = (0..100).any(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            text_manager.get_msg("fr", "greet").as_deref() == Ok("Salut")
+        });
+        assert!(reloaded);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_named_args() {
+        let dir = locale_dir("named_args");
+        std::fs::write(
+            dir.join("en.ftl"),
+            "photos = { $count ->\n    [one] one photo\n   *[other] { $count } photos\n  } of { $day }\n",
+        )
+        .unwrap();
+        let text_manager = TextManager::from_dir(&dir).unwrap();
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 12, 15).unwrap();
+
+        let one = text_manager
+            .msg("en", "photos")
+            .arg("count", 1)
+            .date("day", day)
+            .format()
+            .unwrap();
+        // Without the Unicode isolation marks Fluent puts around placeables
+        let plain = |text: String| text.replace(['\u{2068}', '\u{2069}'], "");
+        assert_eq!(plain(one), "one photo of December 15, 2025");
+        let many = text_manager
+            .msg("en", "photos")
+            .arg("count", 3)
+            .arg("day", "today")
+            .format()
+            .unwrap();
+        assert_eq!(plain(many), "3 photos of today");
+
+        // Missing arguments are reported instead of printed as `{$day}`
+        let result = text_manager.msg("en", "photos").arg("count", 2).format();
+        assert!(matches!(
+            result,
+            Err(TextError::Format { ref id, ref errors, .. }) if id == "photos" && errors.len() == 1
+        ));
+        assert!(text_manager.get_msg("en", "describe-yourself").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_date_format() {
+        let text_manager = TextManager::new().with_fallback(Fallback::Strict);
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 3, 7).unwrap();
+        let format = |lang| {
+            text_manager
+                .msg(lang, "describe-yourself")
+                .date("p1", day)
+                .format()
+                .unwrap()
+        };
+        assert!(format("en").contains("March 7, 2025"));
+        assert!(format("de").contains("07.03.2025"));
+    }
+
+    #[test]
+    fn test_regional_fallback() {
+        let dir = locale_dir("regional");
+        std::fs::write(dir.join("de_ch.ftl"), "greet = Grüezi\n").unwrap();
+        std::fs::write(dir.join("de.ftl"), "greet = Guten Tag\nbye = Tschüss\n").unwrap();
+        let text_manager = TextManager::from_dir(&dir).unwrap();
+
+        assert_eq!(text_manager.languages(), vec!["de", "de-CH", "en"]);
+        assert_eq!(text_manager.fallback_chain("de-ch"), vec!["de-CH", "de", "en"]);
+        assert_eq!(text_manager.fallback_chain("de-AT"), vec!["de", "en"]);
+        assert_eq!(text_manager.fallback_chain("en-GB"), vec!["en"]);
+
+        // Per message: the regional file only overrides what differs
+        assert_eq!(text_manager.get_msg("de-CH", "greet").unwrap(), "Grüezi");
+        assert_eq!(text_manager.get_msg("de-CH", "bye").unwrap(), "Tschüss");
+        assert_eq!(text_manager.get_msg("de-AT", "greet").unwrap(), "Guten Tag");
+        assert!(text_manager.has_msg("de-CH", "object-words"));
+        assert!(!text_manager.has_msg("de-CH", "no-such-words"));
+        assert_eq!(
+            text_manager.get_msg("en-GB", "which-task-for-you"),
+            text_manager.get_msg("en", "which-task-for-you")
+        );
+        assert!(text_manager.has_language("de-AT"));
+        assert!(!text_manager.has_language("ru-RU"));
+
+        // Strict still falls back to the parent language, but not to `en`
+        let strict = TextManager::from_dir(&dir)
+            .unwrap()
+            .with_fallback(Fallback::Strict);
+        assert_eq!(strict.fallback_chain("de-CH"), vec!["de-CH", "de"]);
+        assert_eq!(strict.get_msg("de-CH", "bye").unwrap(), "Tschüss");
+        assert_eq!(
+            strict.get_msg("ru-RU", "greet"),
+            Err(TextError::UnsupportedLanguage("ru-RU".to_string()))
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,752 @@
+use rig_test::helper::{Capability, Host, ModelRegistry};
+
+pub fn main() {
+    let registry = ModelRegistry::builtin();
+    for model in registry.models() {
+        println!(
+            "{:<45} {:<6?} {:>7} {:?}",
+            model.name, model.host, model.context_size, model.capabilities
+        );
+    }
+    match registry
+        .query()
+        .host(Host::Remote)
+        .with(Capability::Tools)
+        .with(Capability::Vision)
+        .first()
+    {
+        Ok(model) => println!("Remote tool capable vision model: {}", model.name),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rig_test::helper::*;
+
+    #[test]
+    fn test_check_model() {
+        assert!(check_model("functiongemma", false));
+        assert!(check_model("qwen3-vl:235b-cloud", true));
+        assert!(!check_model("qwen3-vl:235b-cloud", false));
+        assert!(!check_model("mistral-nemo:12b", false));
+    }
+
+    #[test]
+    fn test_query() {
+        let registry = ModelRegistry::builtin();
+        let model = registry
+            .query()
+            .host(Host::Remote)
+            .with(Capability::Tools)
+            .with(Capability::Vision)
+            .first()
+            .unwrap();
+        assert_eq!(model.name, "qwen3-vl");
+
+        let vision: Vec<&str> = registry
+            .query()
+            .host(Host::Local)
+            .with(Capability::Vision)
+            .all()
+            .iter()
+            .map(|model| model.name.as_str())
+            .collect();
+        assert!(vision.contains(&"llava"));
+        assert!(!vision.contains(&"functiongemma"));
+
+        // Tool calling models, the ones the extractor works with
+        for model in registry.query().with(Capability::Tools).all() {
+            assert!(model.supports(Capability::Tools));
+        }
+        assert!(
+            registry
+                .query()
+                .min_context(100_000)
+                .all()
+                .iter()
+                .all(|model| model.context_size >= 100_000)
+        );
+    }
+
+    #[test]
+    fn test_no_match() {
+        let registry = ModelRegistry::builtin();
+        let result = registry
+            .query()
+            .host(Host::Remote)
+            .with(Capability::Reasoning)
+            .min_context(1_000_000)
+            .first();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No model matches: host Remote, Reasoning, context >= 1000000"
+        );
+    }
+
+    #[test]
+    fn test_require() {
+        let registry = ModelRegistry::builtin();
+        let model = registry
+            .require("qwen3:14b", Host::Remote, &[Capability::Tools])
+            .unwrap();
+        assert_eq!(model.temperature, Some(0.6));
+
+        assert_eq!(
+            registry.require(
+                "llava",
+                Host::Remote,
+                &[Capability::Vision, Capability::Tools]
+            ),
+            Err(ModelError::MissingCapability {
+                name: "llava".to_string(),
+                capability: Capability::Tools
+            })
+        );
+        assert_eq!(
+            registry.require("llava:13b", Host::Local, &[]),
+            Err(ModelError::UnknownModel {
+                name: "llava:13b".to_string(),
+                host: Host::Local
+            })
+        );
+    }
+
+    #[test]
+    fn test_custom_registry() {
+        let registry = ModelRegistry::new(vec![
+            ModelInfo::new("small", Host::Local, &[Capability::Json], 8_192),
+            ModelInfo::new("big", Host::Local, &[Capability::Json], 131_072).with_temperature(0.2),
+        ]);
+        let model = registry.query().min_context(10_000).first().unwrap();
+        assert_eq!(model.name, "big");
+
+        let json = serde_json::to_value(model).unwrap();
+        assert_eq!(json["host"], "local");
+        assert_eq!(json["capabilities"], serde_json::json!(["json"]));
+    }
+
+    const TAGS: &[&str] = &["llava:latest", "qwen3:14b"];
+
+    /// A minimal Ollama: `/api/tags` lists `models`, `/api/show` describes them, failing
+    /// for names with `broken`, and chats
+    /// always answer "Hello", also on the OpenAI-compatible `/v1/models` and
+    /// `/v1/chat/completions`.
+    /// Returns the base URL and the number of requests served.
+    async fn mock_ollama(
+        models: &'static [&'static str],
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut request = Vec::new();
+                let mut buf = [0; 4096];
+                // Headers, then the body announced by Content-Length
+                loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length || n == 0 {
+                            break;
+                        }
+                    }
+                }
+                let request = String::from_utf8_lossy(&request);
+                let body = if request.starts_with("GET /api/tags") {
+                    let models: Vec<_> = models
+                        .iter()
+                        .map(|name| serde_json::json!({ "name": name }))
+                        .collect();
+                    serde_json::json!({ "models": models })
+                } else if request.contains("llava") {
+                    serde_json::json!({
+                        "capabilities": ["completion", "vision"],
+                        "model_info": { "llama.context_length": 4096 }
+                    })
+                } else {
+                    // An older server without capabilities
+                    serde_json::json!({ "model_info": { "qwen3.context_length": 40960 } })
+                };
+                let (status, body) = if request.starts_with("POST /api/show")
+                    && request.contains("broken")
+                {
+                    ("500 Internal Server Error", String::new())
+                } else if request.starts_with("GET /api/tags")
+                    || request.starts_with("POST /api/show")
+                {
+                    ("200 OK", body.to_string())
+                } else if request.starts_with("GET /v1/models") {
+                    let models: Vec<_> = models
+                        .iter()
+                        .map(|name| serde_json::json!({ "id": name, "object": "model" }))
+                        .collect();
+                    let body = serde_json::json!({ "object": "list", "data": models });
+                    ("200 OK", body.to_string())
+                } else if request.starts_with("POST /api/chat") {
+                    let message = |thinking: &str, content: &str, done: bool| {
+                        serde_json::json!({
+                            "model": "llava",
+                            "created_at": "2025-01-01T00:00:00Z",
+                            "message": { "role": "assistant", "content": content, "thinking": thinking },
+                            "done": done,
+                            "prompt_eval_count": 3,
+                            "eval_count": 2
+                        })
+                    };
+                    let body = if request.contains(r#""stream":true"#) {
+                        [
+                            message("Greet.", "Hel", false),
+                            message("", "lo", false),
+                            message("", "", true),
+                        ]
+                        .map(|line| line.to_string())
+                        .join("\n")
+                    } else {
+                        message("", "Hello", true).to_string()
+                    };
+                    ("200 OK", body)
+                } else if request.starts_with("POST /v1/chat/completions") {
+                    let body = serde_json::json!({
+                        "id": "chatcmpl-1",
+                        "object": "chat.completion",
+                        "created": 0,
+                        "model": "llava",
+                        "system_fingerprint": null,
+                        "choices": [{
+                            "index": 0,
+                            "message": { "role": "assistant", "content": "Hello" },
+                            "logprobs": null,
+                            "finish_reason": "stop"
+                        }],
+                        "usage": { "prompt_tokens": 3, "total_tokens": 5 }
+                    });
+                    ("200 OK", body.to_string())
+                } else {
+                    ("404 Not Found", String::new())
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn test_discovery_merges_declared() {
+        let (url, _) = mock_ollama(TAGS).await;
+        let discovery = ModelDiscovery::new(&url, Host::Remote);
+        let registry = discovery.registry().await.unwrap();
+
+        let names: Vec<&str> = registry.models().iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["llava:latest", "qwen3:14b"]);
+
+        // Reported by the server
+        let llava = discovery
+            .require("llava", &[Capability::Vision])
+            .await
+            .unwrap();
+        assert_eq!(llava.context_size, 4096);
+        assert!(llava.supports(Capability::Json));
+        // Declared only, the server does not report capabilities
+        let qwen = discovery
+            .require("qwen3:14b", &[Capability::Tools])
+            .await
+            .unwrap();
+        assert_eq!(qwen.context_size, 40960);
+        assert_eq!(qwen.temperature, Some(0.6));
+    }
+
+    #[tokio::test]
+    async fn test_discovery_errors() {
+        let (url, _) = mock_ollama(TAGS).await;
+        let discovery = ModelDiscovery::new(&url, Host::Remote);
+
+        let error = discovery.require("gemma3:12b", &[]).await.unwrap_err();
+        assert!(
+            matches!(error, DiscoveryError::MissingModel { ref name, .. } if name == "gemma3:12b")
+        );
+        assert!(
+            error
+                .to_string()
+                .contains("available: llava:latest, qwen3:14b")
+        );
+
+        assert!(matches!(
+            discovery.require("llava", &[Capability::Tools]).await,
+            Err(DiscoveryError::Model(ModelError::MissingCapability { .. }))
+        ));
+
+        let unreachable = ModelDiscovery::new("http://127.0.0.1:1", Host::Local);
+        assert!(matches!(
+            unreachable.registry().await,
+            Err(DiscoveryError::Http { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_discovery_partial() {
+        let (url, _) = mock_ollama(&["llava", "broken:7b"]).await;
+        let discovery = ModelDiscovery::new(&url, Host::Remote);
+
+        // A model the server cannot describe is kept without capabilities
+        let names: Vec<String> = discovery
+            .registry()
+            .await
+            .unwrap()
+            .models()
+            .iter()
+            .map(|m| m.name.clone())
+            .collect();
+        assert_eq!(names, ["llava:latest", "broken:7b"]);
+        let broken = discovery.require("broken:7b", &[]).await.unwrap();
+        assert!(broken.capabilities.is_empty());
+        assert!(
+            discovery
+                .require("llava", &[Capability::Vision])
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discovery_cache() {
+        let (url, requests) = mock_ollama(TAGS).await;
+        let count = || requests.load(std::sync::atomic::Ordering::SeqCst);
+
+        let discovery = ModelDiscovery::new(&url, Host::Remote);
+        discovery.registry().await.unwrap();
+        // One listing and one show per model
+        assert_eq!(count(), 3);
+        discovery.require("llava", &[]).await.unwrap();
+        assert_eq!(count(), 3);
+        discovery.invalidate();
+        discovery.registry().await.unwrap();
+        assert_eq!(count(), 6);
+
+        let discovery = ModelDiscovery::new(&url, Host::Remote).with_ttl(std::time::Duration::ZERO);
+        discovery.registry().await.unwrap();
+        discovery.registry().await.unwrap();
+        assert_eq!(count(), 12);
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = ClientConfig::default();
+        assert_eq!(config.default_endpoint, "remote");
+        assert_eq!(
+            config.endpoint("local").unwrap().url,
+            "http://localhost:11434"
+        );
+        assert_eq!(
+            config.endpoint(Host::Remote.endpoint()).unwrap().url,
+            "http://localhost:8050"
+        );
+        assert!(config.client("remote").is_ok());
+        assert_eq!(
+            config.default_client().unwrap().base_url(),
+            "http://localhost:8050"
+        );
+        assert_eq!(config.default_model("llava").unwrap(), "llava");
+        assert_eq!(
+            config.client("prod").unwrap_err(),
+            ConfigError::UnknownEndpoint("prod".to_string())
+        );
+    }
+
+    #[test]
+    fn test_config_file() {
+        let config = ClientConfig::from_file("endpoints.example.json").unwrap();
+        let remote = config.endpoint("remote").unwrap();
+        assert_eq!(remote.timeout_secs, Some(600));
+        assert_eq!(remote.default_model.as_deref(), Some("qwen3-vl"));
+        assert_eq!(config.default_model("llava").unwrap(), "qwen3-vl");
+        assert_eq!(config.endpoint("local").unwrap().model("llava"), "llava");
+        assert_eq!(
+            config.endpoint("staging").unwrap().url,
+            "http://staging.internal:11434"
+        );
+        assert!(config.client("staging").is_ok());
+        let llama_cpp = config.endpoint("llama-cpp").unwrap();
+        assert_eq!(llama_cpp.provider, Provider::OpenAi);
+        assert_eq!(
+            config.client("llama-cpp").unwrap().provider(),
+            Provider::OpenAi
+        );
+        assert_eq!(
+            config.ollama_client("llama-cpp").unwrap_err(),
+            ConfigError::NotOllama {
+                endpoint: "llama-cpp".to_string(),
+                provider: Provider::OpenAi
+            }
+        );
+        let endpoints: Vec<&str> = config.endpoints.keys().map(String::as_str).collect();
+        assert!(ClientPool::new(&config, &endpoints).is_ok());
+
+        let path = std::env::temp_dir().join(format!("models_test_{}.json", std::process::id()));
+        // Endpoints not in the file keep their defaults
+        std::fs::write(&path, r#"{ "default_endpoint": "local", "endpoints": {} }"#).unwrap();
+        let config = ClientConfig::from_file(&path).unwrap();
+        assert_eq!(config.default_endpoint, "local");
+        assert_eq!(
+            config,
+            ClientConfig {
+                default_endpoint: "local".to_string(),
+                ..Default::default()
+            }
+        );
+
+        std::fs::write(&path, r#"{ "endpoints": { "local": { "uri": "x" } } }"#).unwrap();
+        assert!(matches!(
+            ClientConfig::from_file(&path),
+            Err(ConfigError::Json { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            ClientConfig::from_file(&path),
+            Err(ConfigError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_config_env() {
+        let mut config = ClientConfig::default();
+        config
+            .apply_env(env(&[
+                ("RIG_TEST_STAGING_EU_MODEL", "qwen3:14b"),
+                ("RIG_TEST_STAGING_EU_URL", "http://eu.staging:11434"),
+                ("RIG_TEST_REMOTE_TIMEOUT_SECS", "120"),
+                ("RIG_TEST_REMOTE_CONNECT_TIMEOUT_SECS", "3"),
+                ("RIG_TEST_ENDPOINT", "staging-eu"),
+                ("RIG_TEST_STAGING_EU_PROVIDER", "OpenAI"),
+                ("RIG_TEST_STAGING_EU_API_KEY", "secret"),
+                ("OLLAMA_HOST", "ignored"),
+            ]))
+            .unwrap();
+        assert_eq!(config.default_endpoint, "staging-eu");
+        let staging = config.endpoint("staging-eu").unwrap();
+        assert_eq!(staging.url, "http://eu.staging:11434");
+        assert_eq!(staging.default_model.as_deref(), Some("qwen3:14b"));
+        assert_eq!(config.default_model("llava").unwrap(), "qwen3:14b");
+        assert_eq!(
+            config.default_client().unwrap().provider(),
+            Provider::OpenAi
+        );
+        assert_eq!(
+            (staging.provider, staging.api_key.as_deref()),
+            (Provider::OpenAi, Some("secret"))
+        );
+        let remote = config.endpoint("remote").unwrap();
+        assert_eq!(
+            (remote.timeout_secs, remote.connect_timeout_secs),
+            (Some(120), Some(3))
+        );
+        assert_eq!(remote.url, "http://localhost:8050");
+
+        assert_eq!(
+            config.apply_env(env(&[("RIG_TEST_LOCAL_TIMEOUT_SECS", "soon")])),
+            Err(ConfigError::InvalidValue {
+                name: "RIG_TEST_LOCAL_TIMEOUT_SECS".to_string(),
+                value: "soon".to_string()
+            })
+        );
+        assert!(matches!(
+            config.apply_env(env(&[("RIG_TEST_LOCAL_PROVIDER", "bedrock")])),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert_eq!(
+            config.apply_env(env(&[("RIG_TEST_PROD_MODEL", "llava")])),
+            Err(ConfigError::UnknownEndpoint("prod".to_string()))
+        );
+    }
+
+    /// Endpoints `a` and `b` serving [`TAGS`], `vl` serving only `qwen3-vl` and `down`
+    /// refusing connections
+    async fn pool_config() -> ClientConfig {
+        let mut config = ClientConfig::default();
+        config
+            .endpoints
+            .insert("a".to_string(), Endpoint::new(&mock_ollama(TAGS).await.0));
+        config
+            .endpoints
+            .insert("b".to_string(), Endpoint::new(&mock_ollama(TAGS).await.0));
+        config.endpoints.insert(
+            "vl".to_string(),
+            Endpoint::new(&mock_ollama(&["qwen3-vl:latest"]).await.0),
+        );
+        config
+            .endpoints
+            .insert("down".to_string(), Endpoint::new("http://127.0.0.1:1"));
+        config
+    }
+
+    /// Answers with the URL of the host, or fails on the URLs in `failing`
+    async fn answer(client: ProviderClient, failing: &[String]) -> Result<String, std::io::Error> {
+        let url = client.base_url().to_string();
+        if failing.contains(&url) {
+            Err(std::io::Error::other(format!("{url} is overloaded")))
+        } else {
+            Ok(url)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_routing() {
+        let config = pool_config().await;
+        let pool = ClientPool::new(&config, &["a", "b", "vl", "down"]).unwrap();
+
+        let mut hosts = pool.hosts_for("llava").await;
+        hosts.sort();
+        assert_eq!(hosts, ["a", "b"]);
+        assert_eq!(pool.hosts_for("qwen3-vl").await, ["vl"]);
+
+        // Round-robin over the hosts serving the model
+        let url = |name: &str| config.endpoint(name).unwrap().url.clone();
+        let mut answered = Vec::new();
+        for _ in 0..4 {
+            answered.push(pool.run("llava", |c| answer(c, &[])).await.unwrap());
+        }
+        assert_ne!(answered[0], answered[1]);
+        assert_eq!(answered[0], answered[2]);
+        assert_eq!(answered[1], answered[3]);
+        assert!(answered.contains(&url("a")) && answered.contains(&url("b")));
+
+        assert!(matches!(
+            pool.run("gemma3:12b", |c| answer(c, &[])).await,
+            Err(PoolError::NoHost(model)) if model == "gemma3:12b"
+        ));
+        assert!(ClientPool::new(&config, &["missing"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pool_mixed_providers() {
+        let mut config = pool_config().await;
+        let (url, _) = mock_ollama(&["llava", "Qwen/Qwen3-8B"]).await;
+        config.endpoints.insert(
+            "cpp".to_string(),
+            Endpoint::new(&format!("{url}/v1")).with_provider(Provider::OpenAi),
+        );
+        let pool = ClientPool::new(&config, &["a", "cpp"]).unwrap();
+
+        let mut hosts = pool.hosts_for("llava").await;
+        hosts.sort();
+        assert_eq!(hosts, ["a", "cpp"]);
+        // Names of OpenAI-compatible servers are taken as they are
+        assert_eq!(pool.hosts_for("Qwen/Qwen3-8B").await, ["cpp"]);
+
+        let mut providers = Vec::new();
+        for _ in 0..2 {
+            providers.push(
+                pool.run("llava", |client| async move {
+                    Ok::<_, std::io::Error>(client.provider())
+                })
+                .await
+                .unwrap(),
+            );
+        }
+        providers.sort_by_key(|provider| *provider == Provider::OpenAi);
+        assert_eq!(providers, [Provider::Ollama, Provider::OpenAi]);
+
+        // Both answer a real prompt through the same agent type
+        let answer = pool
+            .run("llava", |client| async move {
+                use rig::client::CompletionClient;
+                use rig::completion::Prompt;
+                client.agent("llava").build().prompt("Hi").await
+            })
+            .await
+            .unwrap();
+        assert_eq!(answer, "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_pool_failover() {
+        let config = pool_config().await;
+        let pool = ClientPool::new(&config, &["a", "b", "down"])
+            .unwrap()
+            .with_max_failures(2);
+        let a = config.endpoint("a").unwrap().url.clone();
+        let b = config.endpoint("b").unwrap().url.clone();
+
+        for _ in 0..4 {
+            let failing = [a.clone()];
+            assert_eq!(pool.run("llava", |c| answer(c, &failing)).await.unwrap(), b);
+        }
+        let stats = pool.stats();
+        let host = |name: &str| stats.iter().find(|s| s.endpoint == name).unwrap();
+        assert_eq!(host("a").failures, 2);
+        assert!(!host("a").healthy);
+        assert_eq!((host("b").requests, host("b").failures), (4, 0));
+        assert!(host("b").mean_latency.is_some());
+        assert_eq!(host("down").requests, 0);
+        assert_eq!(pool.hosts_for("llava").await, ["b"]);
+
+        // Everything fails, the last error is reported
+        let failing = [a.clone(), b.clone()];
+        match pool.run("llava", |c| answer(c, &failing)).await {
+            Err(PoolError::Failed { model, host, .. }) => {
+                assert_eq!((model.as_str(), host.as_str()), ("llava", "b"))
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        pool.health_check().await;
+        let stats = pool.stats();
+        let healthy = |name: &str| stats.iter().find(|s| s.endpoint == name).unwrap().healthy;
+        assert!(healthy("a") && healthy("b") && !healthy("down"));
+        assert_eq!(pool.stats().iter().map(|s| s.outstanding).sum::<usize>(), 0);
+    }
+
+    /// A server that accepts connections but never answers
+    async fn mock_hanging() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut streams = Vec::new();
+            loop {
+                streams.push(listener.accept().await.unwrap().0);
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_pool_timeout() {
+        let mut config = pool_config().await;
+        let mut hanging = Endpoint::new(&mock_hanging().await);
+        hanging.timeout_secs = Some(1);
+        config.endpoints.insert("hanging".to_string(), hanging);
+        let pool = ClientPool::new(&config, &["a", "hanging"]).unwrap();
+        let a = config.endpoint("a").unwrap().url.clone();
+
+        // Discovery gives up on the hanging host after its timeout
+        let within = std::time::Duration::from_secs(5);
+        let answered = tokio::time::timeout(within, pool.run("llava", |c| answer(c, &[])))
+            .await
+            .expect("run waits for the hanging host");
+        assert_eq!(answered.unwrap(), a);
+
+        tokio::time::timeout(within, pool.health_check())
+            .await
+            .expect("health check waits for the hanging host");
+        let stats = pool.stats();
+        let healthy = |name: &str| stats.iter().find(|s| s.endpoint == name).unwrap().healthy;
+        assert!(healthy("a") && !healthy("hanging"));
+    }
+
+    #[tokio::test]
+    async fn test_pool_least_outstanding() {
+        let config = pool_config().await;
+        let pool = ClientPool::new(&config, &["a", "b"])
+            .unwrap()
+            .with_strategy(Strategy::LeastOutstanding);
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let released = std::sync::Mutex::new(Some(released));
+
+        let slow = pool.run("llava", |client| {
+            let released = released.lock().unwrap().take();
+            async move {
+                if let Some(released) = released {
+                    released.await.unwrap();
+                }
+                Ok::<_, std::io::Error>(client.base_url().to_string())
+            }
+        });
+        let check = async {
+            // The slow request discovers the models first
+            let busy = loop {
+                match pool.stats().into_iter().find(|s| s.outstanding == 1) {
+                    Some(busy) => break busy,
+                    None => tokio::time::sleep(std::time::Duration::from_millis(5)).await,
+                }
+            };
+            // Twice, round-robin alone would start once at the busy host
+            for _ in 0..2 {
+                assert_ne!(pool.hosts_for("llava").await[0], busy.endpoint);
+            }
+            release.send(()).unwrap();
+            busy
+        };
+        let (answered, busy) = tokio::join!(slow, check);
+        assert_eq!(answered.unwrap(), busy.url);
+    }
+
+    #[tokio::test]
+    async fn test_providers() {
+        use futures::StreamExt;
+        use rig::client::CompletionClient;
+        use rig::completion::{CompletionModel, GetTokenUsage, Prompt};
+        use rig::streaming::StreamedAssistantContent;
+
+        let (url, _) = mock_ollama(TAGS).await;
+        let mut config = ClientConfig::default();
+        config
+            .endpoints
+            .insert("ollama".to_string(), Endpoint::new(&url));
+        config.endpoints.insert(
+            "llama-cpp".to_string(),
+            Endpoint::new(&format!("{url}/v1")).with_provider(Provider::OpenAi),
+        );
+
+        // The same agent type whichever API the endpoint speaks
+        for name in ["ollama", "llama-cpp"] {
+            let agent = config
+                .client(name)
+                .unwrap()
+                .agent("llava")
+                .preamble("Be brief.")
+                .build();
+            let answer: String = agent.prompt("Hi").await.unwrap();
+            assert_eq!(answer, "Hello", "{name}");
+        }
+
+        let model = config.client("ollama").unwrap().completion_model("llava");
+        let mut stream = model
+            .stream(model.completion_request("Hi").build())
+            .await
+            .unwrap();
+        let (mut text, mut reasoning, mut last) = (String::new(), String::new(), None);
+        while let Some(chunk) = stream.next().await {
+            match chunk.unwrap() {
+                StreamedAssistantContent::Text(chunk) => text += &chunk.text,
+                StreamedAssistantContent::Reasoning(chunk) => {
+                    reasoning += &chunk.reasoning.concat()
+                }
+                StreamedAssistantContent::Final(response) => last = Some(response),
+                _ => {}
+            }
+        }
+        assert_eq!((text.as_str(), reasoning.as_str()), ("Hello", "Greet."));
+        let last = last.unwrap();
+        assert!(matches!(last, ProviderStreamingResponse::Ollama(_)));
+        assert_eq!(last.token_usage().unwrap().total_tokens, 5);
+        assert!(stream.response.is_some());
+    }
+}
@@ -1,7 +1,6 @@
 use rig::agent::stream_to_stdout;
-use rig::client::Nothing;
 use rig::prelude::*;
-use rig::providers::ollama;
+use rig_test::helper::{default_client, default_model};
 
 use rig::streaming::StreamingPrompt;
 
@@ -13,11 +12,11 @@ async fn main() -> Result<(), anyhow::Error> {
     });
     // Create streaming agent with a single context prompt
 
-    let client: ollama::Client = //ollama::Client::new(Nothing).unwrap();
-    ollama::Client::builder() .api_key(Nothing) .base_url("http://localhost:8050") .build() .unwrap();
+    let client = default_client()?;
+    let model = default_model("llama3.2-vision")?;
 
     let agent = client
-        .agent("llama3.2-vision")
+        .agent(&model)
         .additional_params(json)
         .preamble("Be precise and concise.")
         .temperature(0.5)
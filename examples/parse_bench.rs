@@ -0,0 +1,55 @@
+use rig_test::prompt_context::ContextParser;
+use std::hint::black_box;
+use std::time::Instant;
+
+const PROMPTS: &[&str] = &[
+    "Detect changes during last two weeks",
+    "Show 5 reports",
+    "Build a new report for last month",
+    "Compare all changes in documents from the last three quarters",
+    "Describe the latest image",
+    "What changed since Monday?",
+    "Compare images between the 1st and the 10th",
+    "Show photos from 15 December 2025",
+    "hello world",
+    "Describe every modification of the object in the previous year",
+];
+
+fn main() {
+    let rounds = 2_000;
+    let parser = ContextParser::new();
+
+    // First call compiles and caches the automatons
+    let start = Instant::now();
+    parser.parse("en", PROMPTS[0]).unwrap();
+    println!("First parse (with compiling): {:?}", start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for prompt in PROMPTS {
+            black_box(parser.parse("en", black_box(prompt)).unwrap());
+        }
+    }
+    report("Cached parser", rounds * PROMPTS.len(), start.elapsed());
+
+    // What every call paid before the cache
+    let rounds = rounds / 100;
+    let start = Instant::now();
+    for _ in 0..rounds {
+        for prompt in PROMPTS {
+            black_box(ContextParser::new().parse("en", black_box(prompt)).unwrap());
+        }
+    }
+    report("New parser per prompt", rounds * PROMPTS.len(), start.elapsed());
+}
+
+fn report(name: &str, parses: usize, elapsed: std::time::Duration) {
+    println!(
+        "{}: {} parses in {:?}, {:.1} µs/parse, {:.0} parses/s",
+        name,
+        parses,
+        elapsed,
+        elapsed.as_secs_f64() * 1e6 / parses as f64,
+        parses as f64 / elapsed.as_secs_f64()
+    );
+}
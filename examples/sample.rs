@@ -1,12 +1,12 @@
 use rig::client::CompletionClient;
 use rig::completion::{Prompt, ToolDefinition};
-use rig::providers::ollama;
 use rig::tool::Tool;
 use rig_test::helper::*;
+use rig_test::lang::{LanguageDetector, TextManager};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use tokio::sync::RwLock;
 use tokio::sync::mpsc;
 use uuid::Uuid;
@@ -14,6 +14,9 @@ use rig_test::tools::CXError;
 
 const IS_LOCAL: bool = false;
 
+static DETECTOR: LazyLock<LanguageDetector> =
+    LazyLock::new(|| LanguageDetector::new(&TextManager::new()));
+
 /// ============================================================================
 // EVENT TYPES FOR STREAMING
 // ============================================================================
@@ -101,6 +104,12 @@ pub struct CancellationToken {
     cancelled: Arc<RwLock<bool>>,
 }
 
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CancellationToken {
     pub fn new() -> Self {
         Self {
@@ -134,6 +143,12 @@ pub struct RequestManager {
     active_requests: Arc<RwLock<HashMap<String, CancellationToken>>>,
 }
 
+impl Default for RequestManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RequestManager {
     pub fn new() -> Self {
         Self {
@@ -203,7 +218,12 @@ impl AgentContext {
             user_id: req.user_id,
             chat_id: req.chat_id,
             object_id: req.object_id,
-            language: req.language.unwrap_or_else(|| "en".to_string()),
+            // without a language the message tells, English when it cannot
+            language: req.language.unwrap_or_else(|| {
+                DETECTOR
+                    .detect(&req.message)
+                    .map_or_else(|| "en".to_string(), |d| d.language().to_string())
+            }),
             metadata: req.metadata.unwrap_or(serde_json::json!({})),
             cancellation_token,
         }
@@ -222,14 +242,14 @@ pub struct ChatToolInput {
 
 pub struct ChatToolStreaming {
     context: AgentContext,
-    client: ollama::Client,
+    client: ProviderClient,
     event_tx: mpsc::Sender<StreamEvent>,
 }
 
 impl ChatToolStreaming {
     pub fn new(
         context: AgentContext,
-        client: ollama::Client,
+        client: ProviderClient,
         event_tx: mpsc::Sender<StreamEvent>,
     ) -> Self {
         Self {
@@ -314,14 +334,14 @@ pub struct TaskToolInput {
 
 pub struct TaskToolStreaming {
     context: AgentContext,
-    client: ollama::Client,
+    client: ProviderClient,
     event_tx: mpsc::Sender<StreamEvent>,
 }
 
 impl TaskToolStreaming {
     pub fn new(
         context: AgentContext,
-        client: ollama::Client,
+        client: ProviderClient,
         event_tx: mpsc::Sender<StreamEvent>,
     ) -> Self {
         Self {
@@ -410,14 +430,14 @@ pub struct ObjectToolInput {
 
 pub struct ObjectToolStreaming {
     context: AgentContext,
-    client: ollama::Client,
+    client: ProviderClient,
     event_tx: mpsc::Sender<StreamEvent>,
 }
 
 impl ObjectToolStreaming {
     pub fn new(
         context: AgentContext,
-        client: ollama::Client,
+        client: ProviderClient,
         event_tx: mpsc::Sender<StreamEvent>,
     ) -> Self {
         Self {
@@ -498,14 +518,14 @@ impl Tool for ObjectToolStreaming {
 // ============================================================================
 
 pub struct ChatPipelineStreaming {
-    client: ollama::Client,
+    client: ProviderClient,
     context: AgentContext,
     event_tx: mpsc::Sender<StreamEvent>,
 }
 
 impl ChatPipelineStreaming {
     pub fn new(
-        client: ollama::Client,
+        client: ProviderClient,
         context: AgentContext,
         event_tx: mpsc::Sender<StreamEvent>,
     ) -> Self {
@@ -652,14 +672,14 @@ impl ChatPipelineStreaming {
 }
 
 pub struct TaskPipelineStreaming {
-    client: ollama::Client,
+    client: ProviderClient,
     context: AgentContext,
     event_tx: mpsc::Sender<StreamEvent>,
 }
 
 impl TaskPipelineStreaming {
     pub fn new(
-        client: ollama::Client,
+        client: ProviderClient,
         context: AgentContext,
         event_tx: mpsc::Sender<StreamEvent>,
     ) -> Self {
@@ -757,14 +777,14 @@ impl TaskPipelineStreaming {
 }
 
 pub struct ObjectPipelineStreaming {
-    client: ollama::Client,
+    client: ProviderClient,
     context: AgentContext,
     event_tx: mpsc::Sender<StreamEvent>,
 }
 
 impl ObjectPipelineStreaming {
     pub fn new(
-        client: ollama::Client,
+        client: ProviderClient,
         context: AgentContext,
         event_tx: mpsc::Sender<StreamEvent>,
     ) -> Self {
@@ -873,17 +893,16 @@ impl ObjectPipelineStreaming {
 // ============================================================================
 
 pub struct MasterAgentStreaming {
-    client: ollama::Client,
+    client: ProviderClient,
     request_manager: Arc<RequestManager>,
 }
 
 impl MasterAgentStreaming {
-    pub fn new() -> Self {
-        let client = client(IS_LOCAL);
-        Self {
-            client,
+    pub fn new() -> Result<Self, ConfigError> {
+        Ok(Self {
+            client: client(IS_LOCAL)?,
             request_manager: Arc::new(RequestManager::new()),
-        }
+        })
     }
 
     pub async fn handle_request_stream(
@@ -951,7 +970,7 @@ impl MasterAgentStreaming {
     }
 
     async fn process_request(
-        client: ollama::Client,
+        client: ProviderClient,
         request: AgentRequest,
         context: AgentContext,
         event_tx: mpsc::Sender<StreamEvent>,
@@ -1197,7 +1216,7 @@ mod client_example {
     // Test for ChatTool
     #[tokio::test]
     async fn test_chat_tool_streaming() {
-        let client = client(IS_LOCAL);
+        let client = client(IS_LOCAL).unwrap();
 
         let (tx, mut rx) = mpsc::channel(100);
         let cancellation_token = CancellationToken::new();
@@ -1259,7 +1278,7 @@ mod client_example {
     // Test for TaskTool
     #[tokio::test]
     async fn test_task_tool_streaming() {
-        let client = client(IS_LOCAL);
+        let client = client(IS_LOCAL).unwrap();
 
         let (tx, mut rx) = mpsc::channel(100);
         let cancellation_token = CancellationToken::new();
@@ -1298,7 +1317,7 @@ mod client_example {
     // Test for ObjectTool
     #[tokio::test]
     async fn test_object_tool_streaming() {
-        let client = client(IS_LOCAL);
+        let client = client(IS_LOCAL).unwrap();
 
         let (tx, mut rx) = mpsc::channel(100);
         let cancellation_token = CancellationToken::new();
@@ -1344,7 +1363,7 @@ mod client_example {
     // Test cancellation
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_cancellation() {
-        let client = client(IS_LOCAL);
+        let client = client(IS_LOCAL).unwrap();
 
         let (tx, mut rx) = mpsc::channel(100);
         let cancellation_token = CancellationToken::new();
@@ -1531,6 +1550,33 @@ mod client_example {
 
         assert!(producer_count < 100, "Should be cancelled before 100");
     }
+
+    #[test]
+    fn test_request_language() {
+        let request = |message: &str, language: Option<&str>| AgentRequest {
+            message: message.to_string(),
+            user_id: None,
+            chat_id: None,
+            object_id: None,
+            language: language.map(String::from),
+            session_id: None,
+            metadata: None,
+        };
+        let context = |req| AgentContext::from_request(req, CancellationToken::new());
+
+        assert_eq!(context(request("Zeige mir die Bilder", None)).language, "de");
+        assert_eq!(context(request("Show me the images", None)).language, "en");
+        assert_eq!(context(request("Show me the images", Some("ru"))).language, "ru");
+        assert_eq!(context(request("42", None)).language, "en");
+        // Too short or ambiguous to tell, English rather than a guess
+        for greeting in ["hello", "Hello!", "ok", "hi"] {
+            assert_eq!(context(request(greeting, None)).language, "en", "{greeting}");
+        }
+    }
 }
 
-fn main() {}
+fn main() -> Result<(), ConfigError> {
+    // A broken endpoint config fails here rather than on the first request
+    let _agent = MasterAgentStreaming::new()?;
+    Ok(())
+}
@@ -7,4 +7,62 @@ which-task-for-you = Which of these tasks are you suitable for?
 three-qwestions = I need your help with three types of tasks!
   1. Understanding what's in the image.
   2. Working with tools.
-  3. Thinking.
\ No newline at end of file
+  3. Thinking.
+
+# words ending with * are stems and match longer words when match-prefix is true
+match-prefix = true
+# other words match all their inflected forms through the Snowball stemmer,
+# irregular forms are mapped to the dictionary form first
+stemmer = german
+lemma-forms = verglichen:vergleichen beschrieben:beschreiben verändert:verändern geändert:ändern
+  gemacht:machen erstellt:erstellen gebaut:bauen
+object-words = gebäude* bau* konstruktion* objekt* erstellen anlegen machen
+document-words = bild* foto* video* bericht* dokument* datei* aufnahme*
+description-words = beschreiben beschreibung* schilder* modifikation* abwandlung*
+comparison-words = vergleichen vergleich* unterschied* erkenn* aktualisier* ändern änderung* verändern veränderung*
+last-words = letzte vorige vorherige kürzlich*
+new-words = neu neueste* aktuell*
+all-words = alle jede* gesamt* ganz komplett* sämtlich*
+period-words = tag woche monat quartal jahr
+number-units = null eins zwei drei vier fünf sechs sieben acht neun
+number-teens = zehn elf zwölf dreizehn vierzehn fünfzehn sechzehn siebzehn achtzehn neunzehn
+number-tens = zwanzig dreißig vierzig fünfzig sechzig siebzig achtzig neunzig
+number-hundred = hundert
+number-thousand = tausend
+number-and = und
+# only inside compounds: einundzwanzig, einhundert
+number-prefixes = ein:1
+number-ordinals = erste:1 zweite:2 dritte:3 vierte:4 fünfte:5 sechste:6 siebte:7 achte:8 neunte:9
+  zehnte:10 elfte:11 zwölfte:12 dreizehnte:13 vierzehnte:14 fünfzehnte:15 sechzehnte:16
+  siebzehnte:17 achtzehnte:18 neunzehnte:19 zwanzigste:20 dreißigste:30 vierzigste:40
+  fünfzigste:50 sechzigste:60 siebzigste:70 achtzigste:80 neunzigste:90 hundertste:100
+  tausendste:1000
+# words before a spelled-out ordinal that make it an amount: "der dritte Bericht", not "zum ersten Mal"
+number-ordinal-articles = der die das dem den des am im beim vom
+  mein meine meinem meinen meiner unser unsere unserem unseren
+number-ordinal-endings = n r s m
+# vague quantities as word:value
+amount-vague = paar:3 einige:3 mehrere:3 handvoll:5 dutzend:12
+month-words = januar februar märz april mai juni juli august september oktober november dezember
+weekday-words = montag dienstag mittwoch donnerstag freitag samstag sonntag
+date-today-words = heute
+date-yesterday-words = gestern
+date-ago-words = vor
+date-since-words = seit ab
+date-from-words = von vom
+date-between-words = zwischen
+date-to-words = bis
+date-and-words = und
+date-skip-words = der die das dem den am im ein eine einer einem
+# words that make the next ordinal a day of month: "am 3."
+date-on-words = am
+# words that make the next four-digit number a year: "in 1999"
+date-in-words = in
+# chrono format of date arguments, see TextManager::msg
+date-format = %d.%m.%Y
+negation-words = nicht kein* außer ohne nie
+negation-end-words = sondern nur aber stattdessen
+# negation words that negate the preceding verb when nothing follows them: "Vergleiche nicht"
+negation-trailing-words = nicht
+# frequent words for language detection
+detect-words = der die das den dem des ein eine einen einem einer und oder von vom zu zum zur im in am an auf für mit aus bei ist sind war waren sein es dies diese dieser dieses was welche welcher wer wie warum wann wo zeige zeig mir mich mein meine ich du dein deine bitte kann kannst könnte würde wird hat haben hatte gibt hier gib sag sage finde liste bilder fotos änderungen
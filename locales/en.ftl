@@ -11,13 +11,50 @@ three-qwestions = I need your help with three types of tasks!
   2. Working with tools.
   3. Thinking.
 
-object-words = build construct object create make
-document-words = picture image video report document file
-description-words = describe modification alteration
-comparison-words = compar differ detect update change
-last-words = last previous recent
-new-words = new latest
-all-words = all every entire complete
-period-words = day week month quarter year
-amount_num = 1 2 3 4 5 6 7 8 9 10
-amount_text = one two three four five six seven eight nine ten
+# words ending with * are stems and match longer words when match-prefix is true
+match-prefix = true
+# other words match all their inflected forms through the Snowball stemmer
+stemmer = english
+object-words = build* construct* object* create* make
+document-words = picture* image* video* report* document* file*
+description-words = describ* modif* alteration*
+comparison-words = compar* differ* detect* update* change*
+last-words = last previous recent*
+new-words = new newest latest
+all-words = all every* entire complete
+period-words = day* week* month* quarter* year*
+number-units = zero one two three four five six seven eight nine
+number-teens = ten eleven twelve thirteen fourteen fifteen sixteen seventeen eighteen nineteen
+number-tens = twenty thirty forty fifty sixty seventy eighty ninety
+number-hundred = hundred
+number-thousand = thousand
+number-and = and
+number-ordinals = first:1 second:2 third:3 fourth:4 fifth:5 sixth:6 seventh:7 eighth:8 ninth:9
+  tenth:10 eleventh:11 twelfth:12 thirteenth:13 fourteenth:14 fifteenth:15 sixteenth:16
+  seventeenth:17 eighteenth:18 nineteenth:19 twentieth:20 thirtieth:30 fortieth:40 fiftieth:50
+  sixtieth:60 seventieth:70 eightieth:80 ninetieth:90 hundredth:100 thousandth:1000
+# words before a spelled-out ordinal that make it an amount: "the third report", but not "first compare"
+number-ordinal-articles = the my your our their its this that
+# vague quantities as word:value
+amount-vague = couple:2 pair:2 few:3 several:3 handful:5 dozen:12
+month-words = january february march april may june july august september october november december
+weekday-words = monday tuesday wednesday thursday friday saturday sunday
+date-today-words = today
+date-yesterday-words = yesterday
+date-ago-words = ago
+date-since-words = since
+date-from-words = from
+date-between-words = between
+date-to-words = to till until
+date-and-words = and
+date-skip-words = the a an on at of
+# words that make the next ordinal a day of month: "on the 3rd"
+date-on-words = on
+# words that make the next four-digit number a year: "in 1999"
+date-in-words = in during
+# chrono format of date arguments, see TextManager::msg
+date-format = %B %-d, %Y
+negation-words = not no never except without don't dont doesn't didn't
+negation-end-words = but just only instead rather
+# frequent words for language detection
+detect-words = the a an and or of to in on at for with from by is are was were be been it its this that these those what which who how why when where show me my i you your please can could would will do does did have has had there here give tell find list get see
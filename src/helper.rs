@@ -1,56 +1,75 @@
-use rig::client::Nothing;
-use rig::providers::ollama;
-// visual
-// tool
-// 0..1 thinking: Qwen, DeepSeek
-pub const LOCAL_MODELS: &[&str] = &[
-    "qwen3-vl:235b-cloud",
-    "deepseek-v3.1:671b-cloud",
-    "deepseek-r1",
-    // small models: < 7.8G
-    "llava",
-    "llama3.2-vision",
-    "functiongemma",
-    "adelnazmy2002/Qwen3-VL-4B-Instruct:Q8_0"
-];
-/* removed !!!
-"zeffmuks/universal-ner:latest",
- */
-// 0..2 thinking: Qwen, DeepSeek
-pub const REMOTE_MODELS: &[&str] = &[
-    "qwen3:14b",
-    "qwen3-vl",
-    "deepseek-r1:14b",
-    "ministral-3:14b",
-    "gemma3:12b",
-    "minicpm-v:8b",
-    "llava",
-    "llama3.2-vision",
-    "llava-llama3:latest",
-    "functiongemma",
-    "adelnazmy2002/Qwen3-VL-4B-Instruct:Q8_0"
-];
-/* removed !!!
-"mistral-nemo:12b",
-"iodose/nuextract-v1.5:latest",
-"ALIENTELLIGENCE/structureddataextraction:latest",
- */
-
-pub fn client(is_local: bool) -> ollama::Client {
-    if is_local {
-        ollama::Client::new(Nothing).unwrap()
-    } else {
-        ollama::Client::builder()
-            .api_key(Nothing)
-            .base_url("http://localhost:8050")
-            .build()
-            .unwrap()
-    }
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+
+mod config;
+mod discovery;
+mod models;
+mod pool;
+mod provider;
+
+pub use config::{ClientConfig, ConfigError, DEFAULT_CONFIG_FILE, ENV_PREFIX, Endpoint};
+pub use discovery::{DiscoveryError, ModelDiscovery};
+pub use models::{Capability, Host, ModelError, ModelInfo, ModelQuery, ModelRegistry};
+pub use pool::{ClientPool, HostStats, PoolError, Strategy};
+pub use provider::{
+    Provider, ProviderClient, ProviderModel, ProviderResponse, ProviderStreamingResponse,
+};
+
+/// One discovery per endpoint, so listings are cached across calls
+static DISCOVERIES: LazyLock<Mutex<HashMap<String, Arc<ModelDiscovery>>>> =
+    LazyLock::new(Default::default);
+
+/// The URL of the `local` or `remote` endpoint of [`ClientConfig::global`]
+pub fn base_url(is_local: bool) -> Result<String, ConfigError> {
+    let host = Host::from(is_local);
+    Ok(ClientConfig::global()?.endpoint(host.endpoint())?.url.clone())
+}
+
+/// A client for the `local` or `remote` endpoint of [`ClientConfig::global`],
+/// talking to whichever [`Provider`] the endpoint is configured with
+pub fn client(is_local: bool) -> Result<ProviderClient, ConfigError> {
+    ClientConfig::global()?.client(Host::from(is_local).endpoint())
+}
+
+/// A client for the `default_endpoint` of [`ClientConfig::global`], `RIG_TEST_ENDPOINT` selects it
+pub fn default_client() -> Result<ProviderClient, ConfigError> {
+    ClientConfig::global()?.default_client()
 }
+
+/// The model configured for the `default_endpoint` of [`ClientConfig::global`], `fallback` when none is
+pub fn default_model(fallback: &str) -> Result<String, ConfigError> {
+    Ok(ClientConfig::global()?.default_model(fallback)?.to_string())
+}
+
+/// A pool over all endpoints of [`ClientConfig::global`]
+pub fn pool() -> Result<ClientPool, ConfigError> {
+    let config = ClientConfig::global()?;
+    let endpoints: Vec<&str> = config.endpoints.keys().map(String::as_str).collect();
+    ClientPool::new(config, &endpoints)
+}
+
+/// Whether the model is declared in [`ModelRegistry::builtin`] for the host
 pub fn check_model(model: &str, is_local: bool) -> bool {
-    if is_local {
-        LOCAL_MODELS.contains(&model)
-    } else {
-        REMOTE_MODELS.contains(&model)
-    }
+    ModelRegistry::builtin().get(model, is_local.into()).is_some()
+}
+
+/// Asks the server whether it has the model with all `capabilities`, listings are cached for a minute
+pub async fn check_model_available(
+    model: &str,
+    is_local: bool,
+    capabilities: &[Capability],
+) -> Result<ModelInfo, DiscoveryError> {
+    let config = ClientConfig::global()?;
+    let name = Host::from(is_local).endpoint();
+    let url = config.endpoint(name)?.url.clone();
+    let discovery = match DISCOVERIES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(url)
+    {
+        Entry::Occupied(entry) => entry.get().clone(),
+        Entry::Vacant(entry) => entry.insert(Arc::new(config.discovery(name)?)).clone(),
+    };
+    discovery.require(model, capabilities).await
 }
@@ -0,0 +1,314 @@
+use super::discovery::ModelDiscovery;
+use super::models::Host;
+use super::provider::{Provider, ProviderClient};
+use rig::client::Nothing;
+use rig::providers::{ollama, openai};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Prefix of the environment variables read by [`ClientConfig::load`]
+pub const ENV_PREFIX: &str = "RIG_TEST_";
+/// Read from the working directory when `RIG_TEST_CONFIG` is not set
+pub const DEFAULT_CONFIG_FILE: &str = "endpoints.json";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("Cannot read config '{path}': {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid config '{path}': {message}")]
+    Json { path: String, message: String },
+
+    #[error("Invalid value for {name}: '{value}'")]
+    InvalidValue { name: String, value: String },
+
+    #[error("Unknown endpoint: {0}")]
+    UnknownEndpoint(String),
+
+    #[error("Endpoint '{endpoint}' is {provider:?}, not Ollama")]
+    NotOllama {
+        endpoint: String,
+        provider: Provider,
+    },
+
+    #[error("Cannot build client for endpoint '{endpoint}': {message}")]
+    Client { endpoint: String, message: String },
+}
+
+/// One model server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Endpoint {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "is_ollama")]
+    pub provider: Provider,
+    /// Sent as bearer token to OpenAI-compatible servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Whole request, generation included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// Model used when a caller does not name one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+}
+
+impl Endpoint {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            provider: Provider::Ollama,
+            api_key: None,
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            default_model: None,
+        }
+    }
+
+    pub fn with_provider(mut self, provider: Provider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// The `default_model` of the endpoint, `fallback` when it has none
+    pub fn model<'a>(&'a self, fallback: &'a str) -> &'a str {
+        self.default_model.as_deref().unwrap_or(fallback)
+    }
+}
+
+fn is_ollama(provider: &Provider) -> bool {
+    *provider == Provider::Ollama
+}
+
+/// Named endpoints from `endpoints.json` and `RIG_TEST_*` variables:
+///
+/// ```json
+/// { "default_endpoint": "remote",
+///   "endpoints": { "remote": { "url": "http://localhost:8050", "timeout_secs": 300 },
+///                  "llama-cpp": { "url": "http://localhost:8080/v1", "provider": "openai" } } }
+/// ```
+///
+/// - `RIG_TEST_CONFIG`: path of the file, which must exist then
+/// - `RIG_TEST_ENDPOINT`: the default endpoint
+/// - `RIG_TEST_<NAME>_URL`, `_PROVIDER`, `_API_KEY`, `_TIMEOUT_SECS`, `_CONNECT_TIMEOUT_SECS`,
+///   `_MODEL`: override or add the endpoint `<name>`, lower case with `_` for `-`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    #[serde(default = "default_endpoint")]
+    pub default_endpoint: String,
+    #[serde(default)]
+    pub endpoints: BTreeMap<String, Endpoint>,
+}
+
+fn default_endpoint() -> String {
+    "remote".to_string()
+}
+
+impl Default for ClientConfig {
+    /// `local` on the Ollama default port and `remote` forwarded to `localhost:8050`
+    fn default() -> Self {
+        Self {
+            default_endpoint: default_endpoint(),
+            endpoints: BTreeMap::from([
+                ("local".to_string(), Endpoint::new("http://localhost:11434")),
+                ("remote".to_string(), Endpoint::new("http://localhost:8050")),
+            ]),
+        }
+    }
+}
+
+static GLOBAL: LazyLock<Result<ClientConfig, ConfigError>> = LazyLock::new(ClientConfig::load);
+
+impl ClientConfig {
+    /// The config of this process, loaded once
+    pub fn global() -> Result<&'static ClientConfig, ConfigError> {
+        GLOBAL.as_ref().map_err(Clone::clone)
+    }
+
+    /// The defaults, then the config file, then the environment
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = std::env::var(format!("{ENV_PREFIX}CONFIG")).ok();
+        let mut config = match &path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(DEFAULT_CONFIG_FILE)?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(std::env::vars())?;
+        Ok(config)
+    }
+
+    /// Endpoints of the file replace the defaults of the same name, others are kept
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| ConfigError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::from_json(&content).map_err(|message| ConfigError::Json {
+            path: path.display().to_string(),
+            message,
+        })
+    }
+
+    fn from_json(json: &str) -> Result<Self, String> {
+        let file: ClientConfig = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut config = Self {
+            default_endpoint: file.default_endpoint,
+            ..Self::default()
+        };
+        config.endpoints.extend(file.endpoints);
+        Ok(config)
+    }
+
+    /// Applies `RIG_TEST_*` variables, taken as a parameter to be testable
+    pub fn apply_env(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<(), ConfigError> {
+        const FIELDS: [&str; 6] = [
+            "_CONNECT_TIMEOUT_SECS",
+            "_TIMEOUT_SECS",
+            "_URL",
+            "_PROVIDER",
+            "_API_KEY",
+            "_MODEL",
+        ];
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        // URLs first, they may add the endpoint the other fields refer to
+        vars.sort_by_key(|(name, _)| !name.ends_with("_URL"));
+
+        for (name, value) in vars {
+            let key = &name[ENV_PREFIX.len()..];
+            if key == "ENDPOINT" {
+                self.default_endpoint = value;
+                continue;
+            }
+            let Some((endpoint, field)) = FIELDS
+                .iter()
+                .find_map(|field| Some((key.strip_suffix(field)?, *field)))
+            else {
+                continue;
+            };
+            let endpoint = endpoint.to_lowercase().replace('_', "-");
+            let invalid = || ConfigError::InvalidValue {
+                name: name.clone(),
+                value: value.clone(),
+            };
+            let secs = || value.parse::<u64>().map_err(|_| invalid());
+            if field == "_URL" {
+                self.endpoints
+                    .entry(endpoint)
+                    .and_modify(|e| e.url = value.clone())
+                    .or_insert_with(|| Endpoint::new(&value));
+                continue;
+            }
+            let endpoint = self
+                .endpoints
+                .get_mut(&endpoint)
+                .ok_or_else(|| ConfigError::UnknownEndpoint(endpoint.clone()))?;
+            match field {
+                "_TIMEOUT_SECS" => endpoint.timeout_secs = Some(secs()?),
+                "_CONNECT_TIMEOUT_SECS" => endpoint.connect_timeout_secs = Some(secs()?),
+                "_PROVIDER" => {
+                    endpoint.provider = value.to_lowercase().parse().map_err(|_| invalid())?
+                }
+                "_API_KEY" => endpoint.api_key = Some(value.clone()),
+                _ => endpoint.default_model = Some(value.clone()),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn endpoint(&self, name: &str) -> Result<&Endpoint, ConfigError> {
+        self.endpoints
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownEndpoint(name.to_string()))
+    }
+
+    /// A client for the endpoint with its provider and timeouts
+    pub fn client(&self, name: &str) -> Result<ProviderClient, ConfigError> {
+        let endpoint = self.endpoint(name)?;
+        match endpoint.provider {
+            Provider::Ollama => self.ollama_client(name).map(ProviderClient::from),
+            Provider::OpenAi => {
+                // llama.cpp and vLLM accept any key unless started with one
+                let api_key = endpoint.api_key.as_deref().unwrap_or("none");
+                openai::CompletionsClient::<reqwest::Client>::builder()
+                    .api_key(api_key)
+                    .base_url(&endpoint.url)
+                    .http_client(http_client(name, endpoint)?)
+                    .build()
+                    .map(ProviderClient::from)
+                    .map_err(|e| client_error(name, e))
+            }
+        }
+    }
+
+    /// A client for the `default_endpoint`
+    pub fn default_client(&self) -> Result<ProviderClient, ConfigError> {
+        self.client(&self.default_endpoint)
+    }
+
+    /// The `default_model` of the `default_endpoint`, `fallback` when it has none
+    pub fn default_model<'a>(&'a self, fallback: &'a str) -> Result<&'a str, ConfigError> {
+        Ok(self.endpoint(&self.default_endpoint)?.model(fallback))
+    }
+
+    /// Discovers the models of the endpoint with its provider and timeouts
+    pub fn discovery(&self, name: &str) -> Result<ModelDiscovery, ConfigError> {
+        let endpoint = self.endpoint(name)?;
+        Ok(ModelDiscovery::with_client(
+            &endpoint.url,
+            Host::from_endpoint(name),
+            http_client(name, endpoint)?,
+        )
+        .with_provider(endpoint.provider))
+    }
+
+    /// An Ollama client for the endpoint, for the Ollama API beyond completions
+    pub fn ollama_client(&self, name: &str) -> Result<ollama::Client, ConfigError> {
+        let endpoint = self.endpoint(name)?;
+        if endpoint.provider != Provider::Ollama {
+            return Err(ConfigError::NotOllama {
+                endpoint: name.to_string(),
+                provider: endpoint.provider,
+            });
+        }
+        ollama::Client::<reqwest::Client>::builder()
+            .api_key(Nothing)
+            .base_url(&endpoint.url)
+            .http_client(http_client(name, endpoint)?)
+            .build()
+            .map_err(|e| client_error(name, e))
+    }
+}
+
+fn client_error(endpoint: &str, error: impl ToString) -> ConfigError {
+    ConfigError::Client {
+        endpoint: endpoint.to_string(),
+        message: error.to_string(),
+    }
+}
+
+fn http_client(name: &str, endpoint: &Endpoint) -> Result<reqwest::Client, ConfigError> {
+    let mut http = reqwest::Client::builder();
+    if let Some(secs) = endpoint.timeout_secs {
+        http = http.timeout(Duration::from_secs(secs));
+    }
+    if let Some(secs) = endpoint.connect_timeout_secs {
+        http = http.connect_timeout(Duration::from_secs(secs));
+    }
+    http.build().map_err(|e| client_error(name, e))
+}
@@ -0,0 +1,275 @@
+use super::config::ConfigError;
+use super::models::{Capability, Host, ModelError, ModelInfo, ModelRegistry};
+use super::provider::Provider;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DiscoveryError {
+    #[error("Request to {url} failed: {source}")]
+    Http {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("Model {name} is not on the {host:?} server {url}, available: {}", available.join(", "))]
+    MissingModel {
+        name: String,
+        host: Host,
+        url: String,
+        available: Vec<String>,
+    },
+
+    #[error(transparent)]
+    Model(#[from] ModelError),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// `GET /api/tags`
+#[derive(Deserialize)]
+struct Tags {
+    models: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+}
+
+/// `GET /models` of OpenAI-compatible servers
+#[derive(Deserialize)]
+struct Models {
+    data: Vec<Model>,
+}
+
+#[derive(Deserialize)]
+struct Model {
+    id: String,
+}
+
+/// `POST /api/show`, `capabilities` is missing before Ollama 0.6.4
+#[derive(Deserialize)]
+struct Show {
+    #[serde(default)]
+    capabilities: Option<Vec<String>>,
+    #[serde(default)]
+    model_info: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The models a server actually has, merged with the declared registry
+/// and cached for a while
+pub struct ModelDiscovery {
+    base_url: String,
+    host: Host,
+    provider: Provider,
+    declared: ModelRegistry,
+    ttl: Duration,
+    http: reqwest::Client,
+    cache: Mutex<Option<(Instant, Arc<ModelRegistry>)>>,
+}
+
+impl ModelDiscovery {
+    /// Discovers the models of the server at `base_url`, e.g. `http://localhost:11434`
+    pub fn new(base_url: &str, host: Host) -> Self {
+        Self::with_client(base_url, host, reqwest::Client::new())
+    }
+
+    /// Like [`ModelDiscovery::new`], asking the server through `http`, e.g. with the timeouts
+    /// of the endpoint, see [`ClientConfig::discovery`](super::ClientConfig::discovery)
+    pub fn with_client(base_url: &str, host: Host, http: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            host,
+            provider: Provider::Ollama,
+            declared: ModelRegistry::builtin().clone(),
+            ttl: Duration::from_secs(60),
+            http,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// The API of the server, by default Ollama. OpenAI-compatible servers only list
+    /// model names, everything else comes from the declared models.
+    pub fn with_provider(mut self, provider: Provider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    /// Replaces the declared models, by default [`ModelRegistry::builtin`]
+    pub fn with_registry(mut self, declared: ModelRegistry) -> Self {
+        self.declared = declared;
+        self
+    }
+
+    /// How long a listing is reused, by default a minute
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The models on the server. Capabilities and context size come from the server when it
+    /// reports them, the rest like the temperature from the declared model of the same name.
+    pub async fn registry(&self) -> Result<Arc<ModelRegistry>, DiscoveryError> {
+        if let Some((fetched, registry)) = self.lock().as_ref()
+            && fetched.elapsed() < self.ttl
+        {
+            return Ok(registry.clone());
+        }
+        let registry = Arc::new(self.fetch().await?);
+        *self.lock() = Some((Instant::now(), registry.clone()));
+        Ok(registry)
+    }
+
+    /// The model if the server has it with all `capabilities`
+    pub async fn require(
+        &self,
+        name: &str,
+        capabilities: &[Capability],
+    ) -> Result<ModelInfo, DiscoveryError> {
+        let registry = self.registry().await?;
+        let name = self.canonical(name);
+        if registry.get(&name, self.host).is_none() {
+            return Err(DiscoveryError::MissingModel {
+                name,
+                host: self.host,
+                url: self.base_url.clone(),
+                available: registry.models().iter().map(|m| m.name.clone()).collect(),
+            });
+        }
+        Ok(registry.require(&name, self.host, capabilities)?.clone())
+    }
+
+    /// Forgets the cached listing, the next call asks the server again
+    pub fn invalidate(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<(Instant, Arc<ModelRegistry>)>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn fetch(&self) -> Result<ModelRegistry, DiscoveryError> {
+        if self.provider == Provider::OpenAi {
+            let url = format!("{}/models", self.base_url);
+            let models: Models = self.send(self.http.get(&url), &url).await?;
+            let models = models
+                .data
+                .iter()
+                .map(|model| self.declared(&model.id))
+                .collect();
+            return Ok(ModelRegistry::new(models));
+        }
+
+        let url = format!("{}/api/tags", self.base_url);
+        let tags: Tags = self.send(self.http.get(&url), &url).await?;
+
+        let url = format!("{}/api/show", self.base_url);
+        let shows = futures::future::join_all(tags.models.iter().map(|tag| {
+            let request = self
+                .http
+                .post(&url)
+                .json(&serde_json::json!({ "model": tag.name }));
+            self.send::<Show>(request, &url)
+        }))
+        .await;
+
+        let models = tags
+            .models
+            .iter()
+            .zip(shows)
+            .map(|(tag, show)| match show {
+                Ok(show) => self.merge(&tag.name, show),
+                // Still listed, with only what is declared about it
+                Err(e) => {
+                    tracing::warn!("No details on {}: {}", tag.name, e);
+                    self.declared(&tag.name)
+                }
+            })
+            .collect();
+        Ok(ModelRegistry::new(models))
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+        url: &str,
+    ) -> Result<T, DiscoveryError> {
+        let http = |source| DiscoveryError::Http {
+            url: url.to_string(),
+            source,
+        };
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(http)?
+            .json()
+            .await
+            .map_err(http)
+    }
+
+    /// The declared model of that name, or one without capabilities
+    fn declared(&self, name: &str) -> ModelInfo {
+        let name = self.canonical(name);
+        let declared = self
+            .declared
+            .models()
+            .iter()
+            .find(|model| model.host == self.host && self.canonical(&model.name) == name);
+        let mut model = declared
+            .cloned()
+            .unwrap_or_else(|| ModelInfo::new(&name, self.host, &[], 0));
+        model.name = name;
+        model
+    }
+
+    fn merge(&self, name: &str, show: Show) -> ModelInfo {
+        let mut model = self.declared(name);
+        if let Some(reported) = show.capabilities {
+            let mut capabilities: BTreeSet<Capability> =
+                reported.iter().filter_map(|c| capability(c)).collect();
+            // Ollama constrains any completion model to JSON with `format`
+            if reported.iter().any(|c| c == "completion") {
+                capabilities.insert(Capability::Json);
+            }
+            model.capabilities = capabilities;
+        }
+        let context_size = show
+            .model_info
+            .iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64());
+        if let Some(context_size) = context_size {
+            model.context_size = context_size.try_into().unwrap_or(u32::MAX);
+        }
+        model
+    }
+
+    /// Ollama names without a tag mean `:latest`
+    fn canonical(&self, name: &str) -> String {
+        if self.provider == Provider::Ollama && !name.contains(':') {
+            format!("{name}:latest")
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+fn capability(reported: &str) -> Option<Capability> {
+    match reported {
+        "vision" => Some(Capability::Vision),
+        "tools" => Some(Capability::Tools),
+        "thinking" => Some(Capability::Reasoning),
+        _ => None,
+    }
+}
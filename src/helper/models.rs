@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::LazyLock;
+use strum_macros::{EnumIter, IntoStaticStr};
+use thiserror::Error;
+
+/// Where a model runs, see [`client`](super::client)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Host {
+    /// The `local` endpoint, by default Ollama on its default port
+    Local,
+    /// The `remote` endpoint, by default Ollama forwarded to `localhost:8050`
+    Remote,
+}
+
+impl Host {
+    /// The endpoint of [`ClientConfig`](super::ClientConfig) serving this host
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            Host::Local => "local",
+            Host::Remote => "remote",
+        }
+    }
+
+    /// `local` is the local host, any other endpoint a remote server
+    pub fn from_endpoint(endpoint: &str) -> Self {
+        if endpoint == "local" {
+            Host::Local
+        } else {
+            Host::Remote
+        }
+    }
+}
+
+impl From<bool> for Host {
+    /// `is_local` as used by [`client`](super::client)
+    fn from(is_local: bool) -> Self {
+        if is_local { Host::Local } else { Host::Remote }
+    }
+}
+
+#[derive(
+    Debug,
+    EnumIter,
+    IntoStaticStr,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Capability {
+    /// Accepts images
+    Vision,
+    /// Tool calling, needed by agents with tools and by extractors
+    Tools,
+    /// Thinks before answering
+    Reasoning,
+    /// Structured JSON output
+    Json,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    #[error("Model not found: {name}, host: {host:?}")]
+    UnknownModel { name: String, host: Host },
+
+    #[error("Model {name} does not support {capability:?}")]
+    MissingCapability {
+        name: String,
+        capability: Capability,
+    },
+
+    #[error("No model matches: {0}")]
+    NoMatch(String),
+}
+
+/// A model and what it can do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub name: String,
+    pub host: Host,
+    pub capabilities: BTreeSet<Capability>,
+    /// Context window in tokens
+    pub context_size: u32,
+    /// Recommended temperature, `None` leaves the model default
+    pub temperature: Option<f64>,
+}
+
+impl ModelInfo {
+    pub fn new(name: &str, host: Host, capabilities: &[Capability], context_size: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            host,
+            capabilities: capabilities.iter().copied().collect(),
+            context_size,
+            temperature: None,
+        }
+    }
+
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Declared models in order of preference
+#[derive(Debug, Clone, Default)]
+pub struct ModelRegistry {
+    models: Vec<ModelInfo>,
+}
+
+static BUILTIN: LazyLock<ModelRegistry> = LazyLock::new(|| {
+    use Capability::*;
+    use Host::*;
+
+    ModelRegistry::new(vec![
+        ModelInfo::new(
+            "qwen3-vl:235b-cloud",
+            Local,
+            &[Vision, Tools, Reasoning, Json],
+            262_144,
+        )
+        .with_temperature(0.6),
+        ModelInfo::new(
+            "deepseek-v3.1:671b-cloud",
+            Local,
+            &[Tools, Reasoning, Json],
+            163_840,
+        ),
+        ModelInfo::new("deepseek-r1", Local, &[Reasoning, Json], 131_072).with_temperature(0.6),
+        // small models: < 7.8G
+        ModelInfo::new("llava", Local, &[Vision, Json], 32_768),
+        ModelInfo::new("llama3.2-vision", Local, &[Vision, Json], 131_072),
+        ModelInfo::new("functiongemma", Local, &[Tools, Json], 32_768),
+        ModelInfo::new(
+            "adelnazmy2002/Qwen3-VL-4B-Instruct:Q8_0",
+            Local,
+            &[Vision, Json],
+            262_144,
+        ),
+        ModelInfo::new("qwen3:14b", Remote, &[Tools, Reasoning, Json], 40_960)
+            .with_temperature(0.6),
+        ModelInfo::new(
+            "qwen3-vl",
+            Remote,
+            &[Vision, Tools, Reasoning, Json],
+            262_144,
+        )
+        .with_temperature(0.6),
+        ModelInfo::new("deepseek-r1:14b", Remote, &[Reasoning, Json], 131_072)
+            .with_temperature(0.6),
+        ModelInfo::new("ministral-3:14b", Remote, &[Vision, Tools, Json], 262_144),
+        ModelInfo::new("gemma3:12b", Remote, &[Vision, Json], 131_072).with_temperature(1.0),
+        ModelInfo::new("minicpm-v:8b", Remote, &[Vision, Json], 32_768),
+        ModelInfo::new("llava", Remote, &[Vision, Json], 32_768),
+        ModelInfo::new("llama3.2-vision", Remote, &[Vision, Json], 131_072),
+        ModelInfo::new("llava-llama3:latest", Remote, &[Vision, Json], 8_192),
+        ModelInfo::new("functiongemma", Remote, &[Tools, Json], 32_768),
+        ModelInfo::new(
+            "adelnazmy2002/Qwen3-VL-4B-Instruct:Q8_0",
+            Remote,
+            &[Vision, Json],
+            262_144,
+        ),
+    ])
+});
+
+impl ModelRegistry {
+    pub fn new(models: Vec<ModelInfo>) -> Self {
+        Self { models }
+    }
+
+    /// The models this crate was tried with
+    pub fn builtin() -> &'static ModelRegistry {
+        &BUILTIN
+    }
+
+    pub fn models(&self) -> &[ModelInfo] {
+        &self.models
+    }
+
+    pub fn get(&self, name: &str, host: Host) -> Option<&ModelInfo> {
+        self.models
+            .iter()
+            .find(|model| model.name == name && model.host == host)
+    }
+
+    /// The model if it is declared on `host` with all `capabilities`
+    pub fn require(
+        &self,
+        name: &str,
+        host: Host,
+        capabilities: &[Capability],
+    ) -> Result<&ModelInfo, ModelError> {
+        let model = self
+            .get(name, host)
+            .ok_or_else(|| ModelError::UnknownModel {
+                name: name.to_string(),
+                host,
+            })?;
+        match capabilities
+            .iter()
+            .find(|capability| !model.supports(**capability))
+        {
+            Some(capability) => Err(ModelError::MissingCapability {
+                name: name.to_string(),
+                capability: *capability,
+            }),
+            None => Ok(model),
+        }
+    }
+
+    /// Filters the models, e.g. a tool capable vision model on the remote host:
+    /// `registry.query().host(Host::Remote).with(Capability::Tools).with(Capability::Vision).first()`
+    pub fn query(&self) -> ModelQuery<'_> {
+        ModelQuery {
+            registry: self,
+            host: None,
+            capabilities: BTreeSet::new(),
+            min_context: 0,
+        }
+    }
+}
+
+/// Conditions on a model, created by [`ModelRegistry::query`]
+#[derive(Debug, Clone)]
+pub struct ModelQuery<'a> {
+    registry: &'a ModelRegistry,
+    host: Option<Host>,
+    capabilities: BTreeSet<Capability>,
+    min_context: u32,
+}
+
+impl<'a> ModelQuery<'a> {
+    pub fn host(mut self, host: Host) -> Self {
+        self.host = Some(host);
+        self
+    }
+
+    pub fn with(mut self, capability: Capability) -> Self {
+        self.capabilities.insert(capability);
+        self
+    }
+
+    /// At least `tokens` of context
+    pub fn min_context(mut self, tokens: u32) -> Self {
+        self.min_context = tokens;
+        self
+    }
+
+    pub fn matches(&self, model: &ModelInfo) -> bool {
+        self.host.is_none_or(|host| model.host == host)
+            && self.capabilities.is_subset(&model.capabilities)
+            && model.context_size >= self.min_context
+    }
+
+    /// Matching models in order of preference
+    pub fn all(&self) -> Vec<&'a ModelInfo> {
+        self.registry
+            .models
+            .iter()
+            .filter(|model| self.matches(model))
+            .collect()
+    }
+
+    /// The preferred matching model
+    pub fn first(&self) -> Result<&'a ModelInfo, ModelError> {
+        self.registry
+            .models
+            .iter()
+            .find(|model| self.matches(model))
+            .ok_or_else(|| ModelError::NoMatch(self.to_string()))
+    }
+}
+
+impl std::fmt::Display for ModelQuery<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.host {
+            Some(host) => write!(f, "host {host:?}")?,
+            None => write!(f, "any host")?,
+        }
+        for capability in &self.capabilities {
+            let name: &'static str = capability.into();
+            write!(f, ", {name}")?;
+        }
+        if self.min_context > 0 {
+            write!(f, ", context >= {}", self.min_context)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use super::config::{ClientConfig, ConfigError};
+use super::discovery::ModelDiscovery;
+use super::provider::ProviderClient;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// How [`ClientPool`] orders the hosts serving a model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Each request starts at the next host
+    #[default]
+    RoundRobin,
+    /// The host with the fewest requests in flight first
+    LeastOutstanding,
+}
+
+#[derive(Error, Debug)]
+pub enum PoolError<E: std::error::Error + 'static> {
+    #[error("No healthy host serves model {0}")]
+    NoHost(String),
+
+    #[error("All hosts failed for model {model}, last one {host}: {source}")]
+    Failed {
+        model: String,
+        host: String,
+        #[source]
+        source: E,
+    },
+}
+
+/// A snapshot of one host, see [`ClientPool::stats`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostStats {
+    pub endpoint: String,
+    pub url: String,
+    pub healthy: bool,
+    pub outstanding: usize,
+    pub requests: u64,
+    pub failures: u64,
+    /// Mean duration of the successful requests
+    pub mean_latency: Option<Duration>,
+}
+
+struct Member {
+    endpoint: String,
+    client: ProviderClient,
+    discovery: ModelDiscovery,
+    healthy: AtomicBool,
+    outstanding: AtomicUsize,
+    requests: AtomicU64,
+    failures: AtomicU64,
+    consecutive_failures: AtomicU64,
+    successes: AtomicU64,
+    latency_micros: AtomicU64,
+}
+
+/// Decrements the outstanding requests of a member when dropped
+struct InFlight<'a>(&'a Member);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.outstanding.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Clients for several endpoints serving the same models, of any [`Provider`](super::Provider).
+/// Requests go to the healthy hosts that list the model and fail over to the next one on error.
+pub struct ClientPool {
+    members: Vec<Member>,
+    strategy: Strategy,
+    max_failures: u64,
+    next: AtomicUsize,
+}
+
+impl ClientPool {
+    /// A pool over the named endpoints of `config`
+    pub fn new(config: &ClientConfig, endpoints: &[&str]) -> Result<Self, ConfigError> {
+        let members = endpoints
+            .iter()
+            .map(|name| {
+                Ok(Member {
+                    endpoint: name.to_string(),
+                    client: config.client(name)?,
+                    discovery: config.discovery(name)?,
+                    healthy: AtomicBool::new(true),
+                    outstanding: AtomicUsize::new(0),
+                    requests: AtomicU64::new(0),
+                    failures: AtomicU64::new(0),
+                    consecutive_failures: AtomicU64::new(0),
+                    successes: AtomicU64::new(0),
+                    latency_micros: AtomicU64::new(0),
+                })
+            })
+            .collect::<Result<_, ConfigError>>()?;
+        Ok(Self {
+            members,
+            strategy: Strategy::default(),
+            max_failures: 3,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Failed requests in a row after which a host counts as unhealthy until the
+    /// next successful health check, by default 3
+    pub fn with_max_failures(mut self, max_failures: u64) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Asks every host for its models, a host that does not answer is unhealthy
+    pub async fn health_check(&self) {
+        futures::future::join_all(self.members.iter().map(|member| async move {
+            member.discovery.invalidate();
+            let healthy = member.discovery.registry().await.is_ok();
+            if healthy {
+                member.consecutive_failures.store(0, Ordering::SeqCst);
+            }
+            member.healthy.store(healthy, Ordering::SeqCst);
+        }))
+        .await;
+    }
+
+    /// Runs [`ClientPool::health_check`] every `interval` while the pool is alive
+    pub fn spawn_health_checks(
+        self: &Arc<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let pool = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match pool.upgrade() {
+                    Some(pool) => pool.health_check().await,
+                    None => break,
+                }
+            }
+        })
+    }
+
+    /// Calls `request` with the client of the first suitable host, then the next ones
+    /// until one succeeds
+    pub async fn run<T, E, F, Fut>(&self, model: &str, request: F) -> Result<T, PoolError<E>>
+    where
+        E: std::error::Error + 'static,
+        F: Fn(ProviderClient) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut last_error = None;
+        for member in self.candidates(model).await {
+            member.requests.fetch_add(1, Ordering::SeqCst);
+            member.outstanding.fetch_add(1, Ordering::SeqCst);
+            let _in_flight = InFlight(member);
+            let start = Instant::now();
+            match request(member.client.clone()).await {
+                Ok(response) => {
+                    let micros = start.elapsed().as_micros().try_into().unwrap_or(u64::MAX);
+                    member.latency_micros.fetch_add(micros, Ordering::SeqCst);
+                    member.successes.fetch_add(1, Ordering::SeqCst);
+                    member.consecutive_failures.store(0, Ordering::SeqCst);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    member.failures.fetch_add(1, Ordering::SeqCst);
+                    let in_a_row = member.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+                    if in_a_row >= self.max_failures {
+                        member.healthy.store(false, Ordering::SeqCst);
+                    }
+                    tracing::warn!("{} failed for {}: {}", member.endpoint, model, e);
+                    last_error = Some((member.endpoint.clone(), e));
+                }
+            }
+        }
+        Err(match last_error {
+            Some((host, source)) => PoolError::Failed {
+                model: model.to_string(),
+                host,
+                source,
+            },
+            None => PoolError::NoHost(model.to_string()),
+        })
+    }
+
+    /// Endpoints of the healthy hosts listing `model`, in the order requests try them
+    pub async fn hosts_for(&self, model: &str) -> Vec<String> {
+        self.candidates(model)
+            .await
+            .iter()
+            .map(|member| member.endpoint.clone())
+            .collect()
+    }
+
+    pub fn stats(&self) -> Vec<HostStats> {
+        self.members
+            .iter()
+            .map(|member| {
+                let successes = member.successes.load(Ordering::SeqCst);
+                HostStats {
+                    endpoint: member.endpoint.clone(),
+                    url: member.discovery.base_url().to_string(),
+                    healthy: member.healthy.load(Ordering::SeqCst),
+                    outstanding: member.outstanding.load(Ordering::SeqCst),
+                    requests: member.requests.load(Ordering::SeqCst),
+                    failures: member.failures.load(Ordering::SeqCst),
+                    mean_latency: (successes > 0).then(|| {
+                        Duration::from_micros(
+                            member.latency_micros.load(Ordering::SeqCst) / successes,
+                        )
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    async fn candidates(&self, model: &str) -> Vec<&Member> {
+        let serving = futures::future::join_all(self.members.iter().map(|member| async move {
+            if !member.healthy.load(Ordering::SeqCst) {
+                return None;
+            }
+            match member.discovery.require(model, &[]).await {
+                Ok(_) => Some(member),
+                Err(_) => None,
+            }
+        }))
+        .await;
+        let mut members: Vec<&Member> = serving.into_iter().flatten().collect();
+        if !members.is_empty() {
+            let start = self.next.fetch_add(1, Ordering::SeqCst) % members.len();
+            members.rotate_left(start);
+        }
+        if self.strategy == Strategy::LeastOutstanding {
+            // Stable, so round-robin breaks ties
+            members.sort_by_key(|member| member.outstanding.load(Ordering::SeqCst));
+        }
+        members
+    }
+}
@@ -0,0 +1,190 @@
+use futures::StreamExt;
+use rig::client::CompletionClient;
+use rig::completion::{self, CompletionError, CompletionRequest, GetTokenUsage, Usage};
+use rig::providers::{ollama, openai};
+use rig::streaming::{RawStreamingChoice, StreamedAssistantContent, StreamingCompletionResponse};
+use serde::{Deserialize, Serialize};
+use strum_macros::{EnumString, IntoStaticStr};
+
+/// The API an endpoint speaks
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    IntoStaticStr,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Provider {
+    #[default]
+    Ollama,
+    /// Chat Completions API, e.g. llama.cpp or vLLM with a base URL ending in `/v1`
+    OpenAi,
+}
+
+/// A completion client for any [`Provider`], built by
+/// [`ClientConfig::client`](super::ClientConfig::client). Agents and extractors of it
+/// have the same type whichever server answers.
+#[derive(Debug, Clone)]
+pub enum ProviderClient {
+    Ollama(ollama::Client),
+    OpenAi(openai::CompletionsClient),
+}
+
+impl ProviderClient {
+    pub fn provider(&self) -> Provider {
+        match self {
+            ProviderClient::Ollama(_) => Provider::Ollama,
+            ProviderClient::OpenAi(_) => Provider::OpenAi,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        match self {
+            ProviderClient::Ollama(client) => client.base_url(),
+            ProviderClient::OpenAi(client) => client.base_url(),
+        }
+    }
+}
+
+impl From<ollama::Client> for ProviderClient {
+    fn from(client: ollama::Client) -> Self {
+        ProviderClient::Ollama(client)
+    }
+}
+
+impl From<openai::CompletionsClient> for ProviderClient {
+    fn from(client: openai::CompletionsClient) -> Self {
+        ProviderClient::OpenAi(client)
+    }
+}
+
+impl CompletionClient for ProviderClient {
+    type CompletionModel = ProviderModel;
+}
+
+/// A model of a [`ProviderClient`]
+#[derive(Clone)]
+pub enum ProviderModel {
+    Ollama(ollama::CompletionModel),
+    OpenAi(openai::completion::CompletionModel),
+}
+
+/// The raw response of the provider that answered
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderResponse {
+    Ollama(ollama::CompletionResponse),
+    OpenAi(openai::CompletionResponse),
+}
+
+/// The final chunk of a stream from the provider that answered
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderStreamingResponse {
+    Ollama(ollama::StreamingCompletionResponse),
+    OpenAi(openai::StreamingCompletionResponse),
+}
+
+impl GetTokenUsage for ProviderStreamingResponse {
+    fn token_usage(&self) -> Option<Usage> {
+        match self {
+            ProviderStreamingResponse::Ollama(response) => response.token_usage(),
+            ProviderStreamingResponse::OpenAi(response) => response.token_usage(),
+        }
+    }
+}
+
+impl completion::CompletionModel for ProviderModel {
+    type Response = ProviderResponse;
+    type StreamingResponse = ProviderStreamingResponse;
+    type Client = ProviderClient;
+
+    fn make(client: &Self::Client, model: impl Into<String>) -> Self {
+        match client {
+            ProviderClient::Ollama(client) => ProviderModel::Ollama(client.completion_model(model)),
+            ProviderClient::OpenAi(client) => ProviderModel::OpenAi(client.completion_model(model)),
+        }
+    }
+
+    async fn completion(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<completion::CompletionResponse<ProviderResponse>, CompletionError> {
+        Ok(match self {
+            ProviderModel::Ollama(model) => {
+                wrap_response(model.completion(request).await?, ProviderResponse::Ollama)
+            }
+            ProviderModel::OpenAi(model) => {
+                wrap_response(model.completion(request).await?, ProviderResponse::OpenAi)
+            }
+        })
+    }
+
+    async fn stream(
+        &self,
+        request: CompletionRequest,
+    ) -> Result<StreamingCompletionResponse<ProviderStreamingResponse>, CompletionError> {
+        Ok(match self {
+            ProviderModel::Ollama(model) => wrap_stream(
+                model.stream(request).await?,
+                ProviderStreamingResponse::Ollama,
+            ),
+            ProviderModel::OpenAi(model) => wrap_stream(
+                model.stream(request).await?,
+                ProviderStreamingResponse::OpenAi,
+            ),
+        })
+    }
+}
+
+fn wrap_response<R>(
+    response: completion::CompletionResponse<R>,
+    wrap: fn(R) -> ProviderResponse,
+) -> completion::CompletionResponse<ProviderResponse> {
+    completion::CompletionResponse {
+        choice: response.choice,
+        usage: response.usage,
+        raw_response: wrap(response.raw_response),
+    }
+}
+
+/// Replays the chunks of a provider stream, rig offers no other way to change its type
+fn wrap_stream<R>(
+    response: StreamingCompletionResponse<R>,
+    wrap: fn(R) -> ProviderStreamingResponse,
+) -> StreamingCompletionResponse<ProviderStreamingResponse>
+where
+    R: Clone + Unpin + GetTokenUsage + Send + 'static,
+{
+    let chunks = response.map(move |chunk| {
+        chunk.map(|content| match content {
+            StreamedAssistantContent::Text(text) => RawStreamingChoice::Message(text.text),
+            StreamedAssistantContent::ToolCall(call) => RawStreamingChoice::ToolCall {
+                id: call.id,
+                call_id: call.call_id,
+                name: call.function.name,
+                arguments: call.function.arguments,
+            },
+            StreamedAssistantContent::ToolCallDelta { id, delta } => {
+                RawStreamingChoice::ToolCallDelta { id, delta }
+            }
+            StreamedAssistantContent::Reasoning(reasoning) => RawStreamingChoice::Reasoning {
+                id: reasoning.id,
+                reasoning: reasoning.reasoning.concat(),
+                signature: reasoning.signature,
+            },
+            StreamedAssistantContent::Final(response) => {
+                RawStreamingChoice::FinalResponse(wrap(response))
+            }
+        })
+    });
+    StreamingCompletionResponse::stream(Box::pin(chunks))
+}
@@ -1,9 +1,79 @@
-use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
-use std::collections::HashMap;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentError, FluentResource};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use thiserror::Error;
 use unic_langid::LanguageIdentifier;
 
+mod catalog;
+mod detect;
+mod lint;
+mod message;
+mod watch;
+
+use catalog::Catalog;
+pub use detect::{Detection, LanguageDetector};
+pub use lint::{LintIssue, LintReport, lint_dir, lint_embedded, lint_locales};
+pub use message::MsgBuilder;
+pub use watch::LocaleWatcher;
+
+/// Locales compiled into the binary
+const EMBEDDED_LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.ftl")),
+    ("de", include_str!("../locales/de.ftl")),
+];
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TextError {
+    #[error("Unsupported language: {0}")]
+    UnsupportedLanguage(String),
+
+    #[error("Message '{id}' not found in FTL for language '{lang}'")]
+    MissingMessage { lang: String, id: String },
+
+    #[error("Formatting '{id}' for language '{lang}' failed: {}", join_errors(.errors))]
+    Format {
+        lang: String,
+        id: String,
+        errors: Vec<FluentError>,
+    },
+
+    #[error("Cannot read '{path}': {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid FTL resource '{path}': {message}")]
+    InvalidResource { path: String, message: String },
+
+    #[error("Cannot watch locales: {0}")]
+    Watch(String),
+}
+
+fn join_errors(errors: &[FluentError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// What to do when a language or one of its messages is missing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Fallback {
+    /// Report the error
+    #[default]
+    Strict,
+    /// Take the message from this language instead
+    Language(String),
+}
+
+/// Fluent messages of all languages, the embedded locales optionally extended
+/// by a directory that can be reloaded while running.
+/// Clones share the messages, a reload through one of them is seen by all.
+#[derive(Clone)]
 pub struct TextManager {
-    bundles: HashMap<String, FluentBundle<FluentResource>>,
+    catalog: Arc<RwLock<Arc<Catalog>>>,
+    dir: Option<PathBuf>,
+    fallback: Fallback,
 }
 impl Default for TextManager {
     fn default() -> Self {
@@ -12,42 +82,145 @@ impl Default for TextManager {
 }
 impl TextManager {
     pub fn new() -> Self {
-        let mut bundles = HashMap::new();
+        Self {
+            catalog: Arc::new(RwLock::new(Arc::new(Catalog::embedded()))),
+            dir: None,
+            fallback: Fallback::Language("en".to_string()),
+        }
+    }
+
+    /// The embedded locales plus every `<lang>.ftl` and `<lang>/*.ftl` in `dir`.
+    /// Files are added in path order and their messages override the embedded ones.
+    pub fn from_dir(dir: impl Into<PathBuf>) -> Result<Self, TextError> {
+        let dir = dir.into();
+        Ok(Self {
+            catalog: Arc::new(RwLock::new(Arc::new(Catalog::load(&dir)?))),
+            dir: Some(dir),
+            fallback: Fallback::Language("en".to_string()),
+        })
+    }
 
-        // languages
-        let locales = vec![
-            ("en", include_str!("../locales/en.ftl")),
-            ("de", include_str!("../locales/de.ftl")),
-        ];
+    /// The directory given to [`TextManager::from_dir`]
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
 
-        for (lang_code, content) in locales {
-            let res = FluentResource::try_new(content.to_string()).expect("Error parsing FTL file");
+    /// Reads the directory again and swaps all bundles at once.
+    /// On error the previous messages stay in place.
+    pub fn reload(&self) -> Result<(), TextError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let mut catalog = Catalog::load(dir)?;
+        let mut current = self.catalog.write().unwrap_or_else(PoisonError::into_inner);
+        catalog.generation = current.generation + 1;
+        *current = Arc::new(catalog);
+        Ok(())
+    }
 
-            let lang_id: LanguageIdentifier = lang_code.parse().expect("Wrong language ID");
-            let mut bundle = FluentBundle::new(vec![lang_id]);
-            bundle
-                .add_resource(res)
-                .expect("Failed to add resource to bundle");
+    /// Counts the reloads, so whatever is built from the messages knows when to rebuild
+    pub fn generation(&self) -> u64 {
+        self.catalog().generation
+    }
 
-            bundles.insert(lang_code.to_string(), bundle);
-        }
+    /// Reloads whenever an FTL file in the directory changes, until the watcher is dropped
+    pub fn watch(self: &Arc<Self>) -> Result<LocaleWatcher, TextError> {
+        LocaleWatcher::new(self)
+    }
 
-        Self { bundles }
+    /// The current bundles, unaffected by later reloads
+    fn catalog(&self) -> Arc<Catalog> {
+        self.catalog
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 
-    pub fn get_msg(&self, lang: &str, msg_id: &str) -> String {
-        self.get_msg_with_args(lang, msg_id, FluentArgs::new())
+    /// Replaces the fallback policy, by default missing messages are taken from `en`
+    pub fn with_fallback(mut self, fallback: Fallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    pub fn fallback(&self) -> &Fallback {
+        &self.fallback
+    }
+
+    /// Whether `lang` or one of its parents like `de` for `de-CH` is loaded
+    pub fn has_language(&self, lang: &str) -> bool {
+        let catalog = self.catalog();
+        parents(lang)
+            .iter()
+            .any(|parent| catalog.bundles.contains_key(parent))
+    }
+
+    /// Loaded languages a message of `lang` is looked up in, in order:
+    /// `de-CH` → `de` → `en` with the default fallback
+    pub fn fallback_chain(&self, lang: &str) -> Vec<String> {
+        self.chain(&self.catalog(), lang)
+    }
+
+    /// Loaded languages, sorted
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.catalog().bundles.keys().cloned().collect();
+        languages.sort();
+        languages
+    }
+
+    /// Ids of the messages a language defines itself, in file order
+    pub fn message_ids(&self, lang: &str) -> Vec<String> {
+        self.catalog()
+            .message_ids
+            .get(&parents(lang)[0])
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The language of a text among the loaded ones.
+    /// Builds the word and trigram profiles on every call, keep a [`LanguageDetector`] for many texts.
+    pub fn detect(&self, text: &str) -> Option<Detection> {
+        LanguageDetector::new(self).detect(text)
     }
-    pub fn split_msg(&self, lang: &str, msg_id: &str) -> Vec<String> {
+
+    /// Whether `lang` or one of its parents defines the message, the fallback language is not consulted
+    pub fn has_msg(&self, lang: &str, msg_id: &str) -> bool {
+        let catalog = self.catalog();
+        parents(lang).iter().any(|parent| {
+            catalog
+                .bundles
+                .get(parent)
+                .is_some_and(|bundle| bundle.has_message(msg_id))
+        })
+    }
+
+    /// A message with named arguments of any type:
+    /// `text_manager.msg("en", "photos").arg("count", 3).format()`
+    pub fn msg<'a>(&'a self, lang: &'a str, msg_id: &'a str) -> MsgBuilder<'a> {
+        MsgBuilder::new(self, lang, msg_id)
+    }
+
+    pub fn get_msg(&self, lang: &str, msg_id: &str) -> Result<String, TextError> {
         self.get_msg_with_args(lang, msg_id, FluentArgs::new())
-            .split_whitespace().map(String::from).collect()
     }
-    pub fn get_msg1(&self, lang: &str, msg_id: &str, param1: &str) -> String {
+    pub fn split_msg(&self, lang: &str, msg_id: &str) -> Result<Vec<String>, TextError> {
+        Ok(self
+            .get_msg_with_args(lang, msg_id, FluentArgs::new())?
+            .split_whitespace()
+            .map(String::from)
+            .collect())
+    }
+    pub fn get_msg1(&self, lang: &str, msg_id: &str, param1: &str) -> Result<String, TextError> {
         let mut args = FluentArgs::new();
         args.set("p1", param1);
         self.get_msg_with_args(lang, msg_id, args)
     }
-    pub fn get_msg2(&self, lang: &str, msg_id: &str, param1: &str, param2: &str) -> String {
+    pub fn get_msg2(
+        &self,
+        lang: &str,
+        msg_id: &str,
+        param1: &str,
+        param2: &str,
+    ) -> Result<String, TextError> {
         let mut args = FluentArgs::new();
         args.set("p1", param1);
         args.set("p2", param2);
@@ -60,31 +233,98 @@ impl TextManager {
         param1: &str,
         param2: &str,
         param3: &str,
-    ) -> String {
+    ) -> Result<String, TextError> {
         let mut args = FluentArgs::new();
         args.set("p1", param1);
         args.set("p2", param2);
         args.set("p3", param3);
         self.get_msg_with_args(lang, msg_id, args)
     }
-    /// Builds a prompt string for a specific language and parameters
-    pub fn get_msg_with_args(&self, lang: &str, msg_id: &str, args: FluentArgs) -> String {
-        let bundle = self
-            .bundles
-            .get(lang)
-            .or_else(|| self.bundles.get("en")) // Fallback to English
-            .expect("Language not found");
-
-        let msg = bundle
+    /// Builds a prompt string for a specific language and parameters,
+    /// Fluent errors like a missing argument are reported as [`TextError::Format`]
+    pub fn get_msg_with_args(
+        &self,
+        lang: &str,
+        msg_id: &str,
+        args: FluentArgs,
+    ) -> Result<String, TextError> {
+        let catalog = self.catalog();
+        let bundle = self.bundle_for(&catalog, lang, msg_id)?;
+        let pattern = bundle
             .get_message(msg_id)
-            .unwrap_or_else(|| panic!("Message '{}' not found in FTL", msg_id));
-        //.expect(&format!("Message '{}' not found in FTL", msg_id));
-
-        let pattern = msg.value().expect("Message value is empty");
+            .and_then(|msg| msg.value())
+            .ok_or_else(|| TextError::MissingMessage {
+                lang: lang.to_string(),
+                id: msg_id.to_string(),
+            })?;
         let mut errors = vec![];
-
-        bundle
+        let text = bundle
             .format_pattern(pattern, Some(&args), &mut errors)
-            .to_string()
+            .to_string();
+        if !errors.is_empty() {
+            return Err(TextError::Format {
+                lang: lang.to_string(),
+                id: msg_id.to_string(),
+                errors,
+            });
+        }
+        Ok(text)
+    }
+
+    /// The parents of `lang`, then those of the fallback language, loaded ones only
+    fn chain(&self, catalog: &Catalog, lang: &str) -> Vec<String> {
+        let mut chain = parents(lang);
+        if let Fallback::Language(fallback) = &self.fallback {
+            chain.extend(parents(fallback));
+        }
+        let mut seen = std::collections::HashSet::new();
+        chain.retain(|lang| catalog.bundles.contains_key(lang) && seen.insert(lang.clone()));
+        chain
+    }
+
+    /// The first bundle of the fallback chain holding the message
+    fn bundle_for<'a>(
+        &self,
+        catalog: &'a Catalog,
+        lang: &str,
+        msg_id: &str,
+    ) -> Result<&'a FluentBundle<FluentResource>, TextError> {
+        let bundle = self
+            .chain(catalog, lang)
+            .iter()
+            .filter_map(|lang| catalog.bundles.get(lang))
+            .find(|bundle| bundle.has_message(msg_id));
+        if let Some(bundle) = bundle {
+            return Ok(bundle);
+        }
+        if !parents(lang)
+            .iter()
+            .any(|parent| catalog.bundles.contains_key(parent))
+        {
+            return Err(TextError::UnsupportedLanguage(lang.to_string()));
+        }
+        Err(TextError::MissingMessage {
+            lang: lang.to_string(),
+            id: msg_id.to_string(),
+        })
+    }
+}
+
+/// `lang` in canonical form and its less specific parents: `sr-Latn-RS` → `sr-Latn` → `sr`
+pub(crate) fn parents(lang: &str) -> Vec<String> {
+    let Ok(mut id) = lang.parse::<LanguageIdentifier>() else {
+        return vec![lang.to_string()];
+    };
+    let mut parents = vec![id.to_string()];
+    if id.variants().next().is_some() {
+        id.clear_variants();
+        parents.push(id.to_string());
+    }
+    if id.region.take().is_some() {
+        parents.push(id.to_string());
+    }
+    if id.script.take().is_some() {
+        parents.push(id.to_string());
     }
+    parents
 }
@@ -0,0 +1,140 @@
+use super::{EMBEDDED_LOCALES, TextError};
+use fluent_bundle::FluentResource;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_syntax::ast::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use unic_langid::LanguageIdentifier;
+
+/// The bundles of all languages, replaced as a whole on reload
+pub(super) struct Catalog {
+    pub(super) bundles: HashMap<String, FluentBundle<FluentResource>>,
+    pub(super) message_ids: HashMap<String, Vec<String>>,
+    /// Reloads before this catalog, see [`TextManager::generation`](super::TextManager::generation)
+    pub(super) generation: u64,
+}
+
+impl Catalog {
+    pub(super) fn embedded() -> Self {
+        let mut catalog = Self {
+            bundles: HashMap::new(),
+            message_ids: HashMap::new(),
+            generation: 0,
+        };
+        for (lang, content) in EMBEDDED_LOCALES {
+            catalog
+                .add(lang, content.to_string(), None)
+                .expect("Error parsing FTL file");
+        }
+        catalog
+    }
+
+    /// The embedded locales with every `<lang>.ftl` and `<lang>/*.ftl` of `dir` on top,
+    /// in file name order, later messages override earlier ones
+    pub(super) fn load(dir: &Path) -> Result<Self, TextError> {
+        let mut catalog = Self::embedded();
+        for (lang, path) in locale_files(dir)? {
+            let content = std::fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+            catalog.add(&lang, content, Some(&path))?;
+        }
+        Ok(catalog)
+    }
+
+    fn add(&mut self, lang: &str, content: String, path: Option<&Path>) -> Result<(), TextError> {
+        let origin = || path.map_or_else(|| format!("embedded {lang}.ftl"), display);
+        let res =
+            FluentResource::try_new(content).map_err(|(_, errors)| TextError::InvalidResource {
+                path: origin(),
+                message: errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            })?;
+        let lang_id: LanguageIdentifier = lang.parse().map_err(|_| TextError::InvalidResource {
+            path: origin(),
+            message: format!("'{lang}' is not a language identifier"),
+        })?;
+
+        // `de_ch.ftl` and `de-CH.ftl` are the same language
+        let lang = lang_id.to_string();
+        let ids = self.message_ids.entry(lang.clone()).or_default();
+        for entry in res.entries() {
+            if let Entry::Message(message) = entry
+                && !ids.iter().any(|id| id == message.id.name)
+            {
+                ids.push(message.id.name.to_string());
+            }
+        }
+
+        self.bundles
+            .entry(lang)
+            .or_insert_with(|| FluentBundle::new_concurrent(vec![lang_id]))
+            .add_resource_overriding(res);
+        Ok(())
+    }
+}
+
+/// `(language, source)` of the FTL files in `dir`, the files of one language joined in
+/// the order [`Catalog::load`] adds them
+pub(super) fn locale_sources(dir: &Path) -> Result<Vec<(String, String)>, TextError> {
+    let mut sources: Vec<(String, String)> = Vec::new();
+    for (lang, path) in locale_files(dir)? {
+        let content = std::fs::read_to_string(&path).map_err(|e| io_error(&path, e))?;
+        let lang = lang
+            .parse::<LanguageIdentifier>()
+            .map_or(lang, |id| id.to_string());
+        match sources.iter_mut().find(|(known, _)| *known == lang) {
+            Some((_, source)) => {
+                source.push('\n');
+                source.push_str(&content);
+            }
+            None => sources.push((lang, content)),
+        }
+    }
+    Ok(sources)
+}
+
+/// `(language, path)` of the FTL files in `dir`, sorted by path
+fn locale_files(dir: &Path) -> Result<Vec<(String, PathBuf)>, TextError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        if path.is_dir() {
+            let Some(lang) = file_name(&path) else {
+                continue;
+            };
+            for entry in std::fs::read_dir(&path).map_err(|e| io_error(&path, e))? {
+                let file = entry.map_err(|e| io_error(&path, e))?.path();
+                if is_ftl(&file) {
+                    files.push((lang.clone(), file));
+                }
+            }
+        } else if is_ftl(&path)
+            && let Some(lang) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            files.push((lang.to_string(), path.clone()));
+        }
+    }
+    files.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(files)
+}
+
+fn is_ftl(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|ext| ext == "ftl")
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()?.to_str().map(String::from)
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> TextError {
+    TextError::Io {
+        path: display(path),
+        message: error.to_string(),
+    }
+}
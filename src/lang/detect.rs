@@ -0,0 +1,181 @@
+use super::TextManager;
+use crate::prompt_context::fold;
+use std::collections::{HashMap, HashSet};
+use unic_langid::LanguageIdentifier;
+
+/// The language of a text and how sure the detector is about it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    language: LanguageIdentifier,
+    confidence: f32,
+    margin: f32,
+    score: f32,
+}
+
+impl Detection {
+    pub fn language(&self) -> &LanguageIdentifier {
+        &self.language
+    }
+
+    /// Share of the total score, 0..=1
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Evidence independent of the other languages: 0.7 times the share of known words
+    /// plus 0.3 times the trigram similarity, 0..=1
+    pub fn score(&self) -> f32 {
+        self.score
+    }
+
+    /// Lead of the confidence over the best other language, negative when behind
+    pub fn margin(&self) -> f32 {
+        self.margin
+    }
+}
+
+/// Words and letter trigrams of one locale
+struct Profile {
+    language: LanguageIdentifier,
+    words: HashSet<String>,
+    trigrams: HashMap<[char; 3], f32>,
+}
+
+/// Offline language identification over the locales of a `TextManager`.
+///
+/// Every message of a locale contributes its words and letter trigrams,
+/// the `detect-words` message lists frequent words to help with short prompts.
+/// A prompt is scored by the share of its words known to a locale
+/// and by the similarity of its trigrams to those of the locale.
+pub struct LanguageDetector {
+    profiles: Vec<Profile>,
+    min_score: f32,
+    min_margin: f32,
+}
+
+impl LanguageDetector {
+    pub fn new(text_manager: &TextManager) -> Self {
+        let profiles = text_manager
+            .languages()
+            .into_iter()
+            .filter_map(|lang| {
+                let language = lang.parse::<LanguageIdentifier>().ok()?;
+                let text: Vec<String> = text_manager
+                    .message_ids(&lang)
+                    .iter()
+                    .filter_map(|id| text_manager.get_msg(&lang, id).ok())
+                    .collect();
+                let words: HashSet<String> = words(&text.join(" ")).collect();
+                let trigrams = normalize(trigrams(words.iter().map(String::as_str)));
+                Some(Profile {
+                    language,
+                    words,
+                    trigrams,
+                })
+            })
+            .collect();
+        Self {
+            profiles,
+            min_score: 0.1,
+            min_margin: 0.2,
+        }
+    }
+
+    /// [`Detection::score`] [`detect`](Self::detect) requires, 0.1 by default.
+    /// Texts without a single known word stay below.
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Lead over the runner-up [`detect`](Self::detect) requires, 0.2 by default.
+    /// With two locales that is a confidence of at least 0.6.
+    pub fn with_min_margin(mut self, min_margin: f32) -> Self {
+        self.min_margin = min_margin;
+        self
+    }
+
+    /// The most likely language. `None` when the evidence is too weak, as for "ok",
+    /// or another language is almost as likely, as for "hello".
+    pub fn detect(&self, text: &str) -> Option<Detection> {
+        self.scores(text).into_iter().next().filter(|detection| {
+            detection.score >= self.min_score && detection.margin >= self.min_margin
+        })
+    }
+
+    /// Every language with a positive score, most likely first
+    pub fn scores(&self, text: &str) -> Vec<Detection> {
+        let words: Vec<String> = words(text).collect();
+        if words.is_empty() {
+            return vec![];
+        }
+        let trigrams = normalize(trigrams(words.iter().map(String::as_str)));
+
+        let scores: Vec<(&Profile, f32)> = self
+            .profiles
+            .iter()
+            .map(|profile| {
+                let known = words.iter().filter(|w| profile.words.contains(*w)).count();
+                let vote = known as f32 / words.len() as f32;
+                let similarity: f32 = trigrams
+                    .iter()
+                    .filter_map(|(trigram, weight)| Some(weight * profile.trigrams.get(trigram)?))
+                    .sum();
+                (profile, 0.7 * vote + 0.3 * similarity)
+            })
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        let total: f32 = scores.iter().map(|(_, score)| score).sum();
+
+        let mut detections: Vec<Detection> = scores
+            .into_iter()
+            .map(|(profile, score)| Detection {
+                language: profile.language.clone(),
+                confidence: score / total,
+                margin: 0.0,
+                score,
+            })
+            .collect();
+        detections.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        let first = detections.first().map_or(0.0, |d| d.confidence);
+        let second = detections.get(1).map_or(0.0, |d| d.confidence);
+        for (i, detection) in detections.iter_mut().enumerate() {
+            let best_other = if i == 0 { second } else { first };
+            detection.margin = detection.confidence - best_other;
+        }
+        detections
+    }
+}
+
+/// Folded words made of letters only
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(fold)
+}
+
+/// Letter trigram counts, words are padded so their first and last letters count
+fn trigrams<'a>(words: impl Iterator<Item = &'a str>) -> HashMap<[char; 3], f32> {
+    let mut counts = HashMap::new();
+    for word in words {
+        let padded: Vec<char> = std::iter::once(' ')
+            .chain(word.chars())
+            .chain(std::iter::once(' '))
+            .collect();
+        for window in padded.windows(3) {
+            *counts
+                .entry([window[0], window[1], window[2]])
+                .or_insert(0.0) += 1.0;
+        }
+    }
+    counts
+}
+
+/// Scales to unit length, so the dot product of two profiles is their cosine similarity
+fn normalize(mut counts: HashMap<[char; 3], f32>) -> HashMap<[char; 3], f32> {
+    let length = counts.values().map(|c| c * c).sum::<f32>().sqrt();
+    if length > 0.0 {
+        counts.values_mut().for_each(|c| *c /= length);
+    }
+    counts
+}
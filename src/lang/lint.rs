@@ -0,0 +1,239 @@
+use super::TextError;
+use super::catalog::locale_sources;
+use fluent_syntax::ast::{
+    CallArguments, Entry, Expression, InlineExpression, Pattern, PatternElement,
+};
+use fluent_syntax::parser;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, btree_map};
+use std::path::Path;
+
+/// One problem found in the locales
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum LintIssue {
+    /// The FTL source does not parse, `line` is 1-based
+    SyntaxError {
+        lang: String,
+        line: usize,
+        message: String,
+    },
+    /// The reference language is not among the locales, so nothing could be compared
+    MissingReference { lang: String },
+    /// Defined in the reference language but neither in `lang` nor in a parent like
+    /// `de` for `de-CH`
+    MissingMessage { lang: String, id: String },
+    /// Defined in `lang` but not in the reference language, only a warning
+    /// since language specific messages like `lemma-forms` are optional
+    ExtraMessage { lang: String, id: String },
+    /// Defined more than once in the same language, the bundle keeps the last one
+    DuplicateMessage { lang: String, id: String },
+    /// The variables (`$p1`, ...) differ from the reference language
+    MismatchedVariables {
+        lang: String,
+        id: String,
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+}
+
+impl LintIssue {
+    /// Whether the issue can break formatting at runtime
+    pub fn is_error(&self) -> bool {
+        !matches!(self, LintIssue::ExtraMessage { .. })
+    }
+}
+
+/// Result of [`lint_locales`], serializes to JSON for CI
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LintReport {
+    pub reference: String,
+    pub languages: Vec<String>,
+    pub errors: Vec<LintIssue>,
+    pub warnings: Vec<LintIssue>,
+}
+
+impl LintReport {
+    /// No errors, warnings are allowed
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Variables per message id, terms keep their leading `-`
+type Messages = BTreeMap<String, BTreeSet<String>>;
+
+/// Checks `(lang, source)` pairs against the `reference` language:
+/// syntax errors, missing, extra and duplicate ids and mismatched variables.
+/// A regional language only needs the messages its parent language lacks.
+pub fn lint_locales(locales: &[(&str, &str)], reference: &str) -> LintReport {
+    let mut issues = Vec::new();
+    let mut parsed: BTreeMap<&str, Messages> = BTreeMap::new();
+
+    for (lang, source) in locales {
+        parsed.insert(lang, parse_messages(lang, source, &mut issues));
+    }
+
+    if let Some(expected) = parsed.get(reference) {
+        for (lang, messages) in parsed.iter().filter(|(lang, _)| **lang != reference) {
+            let parents: Vec<&Messages> = super::parents(lang)
+                .iter()
+                .skip(1)
+                .filter_map(|parent| parsed.get(parent.as_str()))
+                .collect();
+            for (id, variables) in expected {
+                match messages.get(id) {
+                    None if parents.iter().any(|parent| parent.contains_key(id)) => {}
+                    None => issues.push(LintIssue::MissingMessage {
+                        lang: lang.to_string(),
+                        id: id.clone(),
+                    }),
+                    Some(found) if found != variables => {
+                        issues.push(LintIssue::MismatchedVariables {
+                            lang: lang.to_string(),
+                            id: id.clone(),
+                            expected: variables.iter().cloned().collect(),
+                            found: found.iter().cloned().collect(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            for id in messages.keys().filter(|id| !expected.contains_key(*id)) {
+                issues.push(LintIssue::ExtraMessage {
+                    lang: lang.to_string(),
+                    id: id.clone(),
+                });
+            }
+        }
+    } else {
+        issues.push(LintIssue::MissingReference {
+            lang: reference.to_string(),
+        });
+    }
+
+    let (errors, warnings) = issues.into_iter().partition(LintIssue::is_error);
+    LintReport {
+        reference: reference.to_string(),
+        languages: parsed.keys().map(|lang| lang.to_string()).collect(),
+        errors,
+        warnings,
+    }
+}
+
+/// Lints the locales built into [`TextManager`](super::TextManager) against `en`
+pub fn lint_embedded() -> LintReport {
+    lint_locales(super::EMBEDDED_LOCALES, "en")
+}
+
+/// Lints the `<lang>.ftl` and `<lang>/*.ftl` files of `dir`, as read by
+/// [`TextManager::from_dir`](super::TextManager::from_dir), against `reference`.
+/// The embedded locales are not included.
+pub fn lint_dir(dir: impl AsRef<Path>, reference: &str) -> Result<LintReport, TextError> {
+    let sources = locale_sources(dir.as_ref())?;
+    let locales: Vec<(&str, &str)> = sources
+        .iter()
+        .map(|(lang, source)| (lang.as_str(), source.as_str()))
+        .collect();
+    Ok(lint_locales(&locales, reference))
+}
+
+fn parse_messages(lang: &str, source: &str, issues: &mut Vec<LintIssue>) -> Messages {
+    let resource = match parser::parse(source) {
+        Ok(resource) => resource,
+        Err((resource, errors)) => {
+            for error in errors {
+                issues.push(LintIssue::SyntaxError {
+                    lang: lang.to_string(),
+                    line: source[..error.pos.start].matches('\n').count() + 1,
+                    message: error.to_string(),
+                });
+            }
+            resource
+        }
+    };
+
+    let mut messages = Messages::new();
+    for entry in &resource.body {
+        let (id, value, attributes) = match entry {
+            Entry::Message(message) => (
+                message.id.name.to_string(),
+                message.value.as_ref(),
+                &message.attributes,
+            ),
+            Entry::Term(term) => (
+                format!("-{}", term.id.name),
+                Some(&term.value),
+                &term.attributes,
+            ),
+            _ => continue,
+        };
+        let mut variables = BTreeSet::new();
+        value
+            .into_iter()
+            .chain(attributes.iter().map(|attribute| &attribute.value))
+            .for_each(|pattern| pattern_variables(pattern, &mut variables));
+
+        // Like the bundle, which adds resources overriding, the last definition wins
+        match messages.entry(id) {
+            btree_map::Entry::Occupied(mut entry) => {
+                issues.push(LintIssue::DuplicateMessage {
+                    lang: lang.to_string(),
+                    id: entry.key().clone(),
+                });
+                entry.insert(variables);
+            }
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(variables);
+            }
+        }
+    }
+    messages
+}
+
+fn pattern_variables(pattern: &Pattern<&str>, variables: &mut BTreeSet<String>) {
+    for element in &pattern.elements {
+        if let PatternElement::Placeable { expression } = element {
+            expression_variables(expression, variables);
+        }
+    }
+}
+
+fn expression_variables(expression: &Expression<&str>, variables: &mut BTreeSet<String>) {
+    match expression {
+        Expression::Select { selector, variants } => {
+            inline_variables(selector, variables);
+            for variant in variants {
+                pattern_variables(&variant.value, variables);
+            }
+        }
+        Expression::Inline(inline) => inline_variables(inline, variables),
+    }
+}
+
+fn inline_variables(inline: &InlineExpression<&str>, variables: &mut BTreeSet<String>) {
+    match inline {
+        InlineExpression::VariableReference { id } => {
+            variables.insert(format!("${}", id.name));
+        }
+        InlineExpression::FunctionReference { arguments, .. } => {
+            argument_variables(arguments, variables)
+        }
+        InlineExpression::TermReference {
+            arguments: Some(arguments),
+            ..
+        } => argument_variables(arguments, variables),
+        InlineExpression::Placeable { expression } => expression_variables(expression, variables),
+        _ => {}
+    }
+}
+
+fn argument_variables(arguments: &CallArguments<&str>, variables: &mut BTreeSet<String>) {
+    for argument in arguments
+        .positional
+        .iter()
+        .chain(arguments.named.iter().map(|named| &named.value))
+    {
+        inline_variables(argument, variables);
+    }
+}
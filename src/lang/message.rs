@@ -0,0 +1,46 @@
+use super::{TextError, TextManager};
+use chrono::NaiveDate;
+use fluent_bundle::{FluentArgs, FluentValue};
+use std::borrow::Cow;
+
+/// Named arguments for one message, created by [`TextManager::msg`]
+pub struct MsgBuilder<'a> {
+    text_manager: &'a TextManager,
+    lang: &'a str,
+    msg_id: &'a str,
+    args: FluentArgs<'a>,
+}
+
+impl<'a> MsgBuilder<'a> {
+    pub(super) fn new(text_manager: &'a TextManager, lang: &'a str, msg_id: &'a str) -> Self {
+        Self {
+            text_manager,
+            lang,
+            msg_id,
+            args: FluentArgs::new(),
+        }
+    }
+
+    /// Sets `$name`, numbers select plural variants like `[one]`
+    pub fn arg(mut self, name: impl Into<Cow<'a, str>>, value: impl Into<FluentValue<'a>>) -> Self {
+        self.args.set(name, value);
+        self
+    }
+
+    /// Sets `$name` to a date written with the `date-format` message of the language,
+    /// ISO 8601 without one
+    pub fn date(self, name: impl Into<Cow<'a, str>>, date: NaiveDate) -> Self {
+        let format = self
+            .text_manager
+            .get_msg(self.lang, "date-format")
+            .unwrap_or_else(|_| "%Y-%m-%d".to_string());
+        let value = date.format(format.trim()).to_string();
+        self.arg(name, value)
+    }
+
+    /// The formatted message, [`TextError::Format`] if Fluent reported errors
+    pub fn format(self) -> Result<String, TextError> {
+        self.text_manager
+            .get_msg_with_args(self.lang, self.msg_id, self.args)
+    }
+}
@@ -0,0 +1,44 @@
+use super::{TextError, TextManager};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::sync::{Arc, Weak};
+
+/// Keeps a [`TextManager`] in sync with its locale directory, stops when dropped
+pub struct LocaleWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl LocaleWatcher {
+    pub(super) fn new(text_manager: &Arc<TextManager>) -> Result<Self, TextError> {
+        let dir = text_manager
+            .dir()
+            .ok_or_else(|| TextError::Watch("no locale directory configured".to_string()))?
+            .to_path_buf();
+        // Weak, so the watcher does not keep the manager alive
+        let text_manager: Weak<TextManager> = Arc::downgrade(text_manager);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => return tracing::warn!("Locale watcher error: {e}"),
+            };
+            let touches_ftl = event
+                .paths
+                .iter()
+                .any(|path| path.extension().is_some_and(|ext| ext == "ftl"));
+            if event.kind.is_access() || !touches_ftl {
+                return;
+            }
+            if let Some(text_manager) = text_manager.upgrade()
+                && let Err(e) = text_manager.reload()
+            {
+                // A half written file, the next event reloads again
+                tracing::warn!("Locales not reloaded: {e}");
+            }
+        })
+        .map_err(|e| TextError::Watch(e.to_string()))?;
+        watcher
+            .watch(&dir, RecursiveMode::Recursive)
+            .map_err(|e| TextError::Watch(e.to_string()))?;
+
+        Ok(Self { _watcher: watcher })
+    }
+}
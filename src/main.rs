@@ -1,3 +1,44 @@
-fn main() {
-    println!("Hello, Rig!");
+use rig_test::lang;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match std::env::args().nth(1).as_deref() {
+        // Prints the JSON report and fails when a locale has issues,
+        // `lint-locales --dir <path>` lints the FTL files of a directory instead
+        Some("lint-locales") => {
+            let report = match std::env::args().nth(2).as_deref() {
+                None => lang::lint_embedded(),
+                Some("--dir") => {
+                    let Some(dir) = std::env::args().nth(3) else {
+                        eprintln!("Usage: lint-locales [--dir <path>]");
+                        return ExitCode::FAILURE;
+                    };
+                    match lang::lint_dir(&dir, "en") {
+                        Ok(report) => report,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            return ExitCode::FAILURE;
+                        }
+                    }
+                }
+                Some(_) => {
+                    eprintln!("Usage: lint-locales [--dir <path>]");
+                    return ExitCode::FAILURE;
+                }
+            };
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("Report serializes")
+            );
+            if report.is_ok() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        _ => {
+            println!("Hello, Rig!");
+            ExitCode::SUCCESS
+        }
+    }
 }
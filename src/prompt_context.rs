@@ -1,9 +1,35 @@
-use aho_corasick::{AhoCorasick, Match, MatchKind};
-use crate::lang::TextManager;
+use crate::lang::{Detection, Fallback, LanguageDetector, TextError, TextManager};
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::borrow::Cow;
+use std::sync::{Arc, PoisonError, RwLock};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, IntoStaticStr};
 use thiserror::Error;
 
+mod dates;
+mod hybrid;
+mod intent;
+mod lexicon;
+mod matcher;
+mod numbers;
+mod vocabulary;
+
+pub use dates::{DateConstraint, DateValue};
+pub use hybrid::{
+    Entity, EntityExtractor, EntityType, ExtractedEntities, HybridContext, HybridExtractor, Provenance,
+    Source,
+};
+pub use intent::{Intent, IntentError, IntentMatch, IntentResolver, IntentRule};
+pub use vocabulary::Vocabulary;
+use dates::DateWords;
+pub(crate) use matcher::fold;
+use matcher::{Folded, KeywordMatcher, Stemmer};
+use numbers::NumberWords;
+
 #[derive(Error, Debug)]
 pub enum ParserError {
     #[error("Failed to build Aho-Corasick automaton: {0}")]
@@ -17,9 +43,33 @@ pub enum ParserError {
 
     #[error("Invalid pattern configuration for key: {0}")]
     InvalidPattern(String),
+
+    #[error("Message '{id}' not found in FTL for language '{lang}'")]
+    MissingMessage { lang: String, id: String },
+
+    #[error("Invalid vocabulary: {0}")]
+    InvalidVocabulary(String),
+
+    #[error("Could not detect the language of the prompt")]
+    UndetectedLanguage,
+
+    #[error(transparent)]
+    Text(TextError),
 }
 
-#[derive(Debug, EnumIter, IntoStaticStr, Copy, Clone, PartialEq, Eq, Hash)]
+impl From<TextError> for ParserError {
+    fn from(error: TextError) -> Self {
+        match error {
+            TextError::UnsupportedLanguage(lang) => ParserError::UnsupportedLanguage(lang),
+            TextError::MissingMessage { lang, id } => ParserError::MissingMessage { lang, id },
+            error => ParserError::Text(error),
+        }
+    }
+}
+
+#[derive(
+    Debug, EnumIter, IntoStaticStr, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub enum Period {
     Day,
     Week,
@@ -28,7 +78,9 @@ pub enum Period {
     Year,
 }
 
-#[derive(Debug, EnumIter, IntoStaticStr, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(
+    Debug, EnumIter, IntoStaticStr, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
 pub enum PromptKey {
     Object,
     Document,
@@ -41,14 +93,163 @@ pub enum PromptKey {
     Amount,
 }
 
-#[derive(Debug, Default, PartialEq)]
+/// What a recognised word stands for
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum Term {
+    Key(PromptKey),
+    /// A key registered with a `Vocabulary`
+    Custom(String),
+    Period(Period),
+    Amount(usize),
+}
+
+/// One occurrence of a recognised word in the prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TermMatch {
+    term: Term,
+    span: Range<usize>,
+    surface: String,
+    negated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    correction: Option<String>,
+    #[serde(default = "exact_confidence", skip_serializing_if = "is_exact")]
+    confidence: f32,
+}
+
+fn exact_confidence() -> f32 {
+    1.0
+}
+
+fn is_exact(confidence: &f32) -> bool {
+    *confidence == 1.0
+}
+
+impl TermMatch {
+    pub fn term(&self) -> Term {
+        self.term.clone()
+    }
+
+    /// Byte range in the original prompt
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The text as written in the prompt
+    pub fn surface(&self) -> &str {
+        &self.surface
+    }
+
+    /// Inside a negation: "not", "except", "ohne"
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// The keyword a typo was corrected to: "compare" for "comapre"
+    pub fn correction(&self) -> Option<&str> {
+        self.correction.as_deref()
+    }
+
+    /// 1 for exact matches, lower the more edits a correction needed
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+/// How keywords are compared with the words of a prompt
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum MatchMode {
+    /// Aho-Corasick over the exact word lists
+    #[default]
+    Exact,
+    /// Exact, then words left over are compared with a bounded edit distance:
+    /// none for up to 3 letters, one for up to 7, two for longer words and stems.
+    /// Words of the language's word list, its stop-words and negations are never corrected
+    Fuzzy,
+}
+
+/// Serialized with a `version` field, see `PromptContext::JSON_VERSION`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(into = "PromptContextJson", try_from = "PromptContextJson")]
 pub struct PromptContext {
     keys: Vec<PromptKey>,
+    excluded: Vec<PromptKey>,
+    custom_keys: Vec<String>,
+    excluded_custom_keys: Vec<String>,
     period: Option<Period>,
     amount: Option<usize>,
+    dates: Vec<DateConstraint>,
+    matches: Vec<TermMatch>,
+}
+
+/// JSON shape of `PromptContext`, fields may be omitted on input
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename = "PromptContext", deny_unknown_fields)]
+struct PromptContextJson {
+    version: u32,
+    #[serde(default)]
+    keys: Vec<PromptKey>,
+    #[serde(default)]
+    excluded: Vec<PromptKey>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    custom_keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    excluded_custom_keys: Vec<String>,
+    #[serde(default)]
+    period: Option<Period>,
+    #[serde(default)]
+    amount: Option<usize>,
+    #[serde(default)]
+    dates: Vec<DateConstraint>,
+    #[serde(default)]
+    matches: Vec<TermMatch>,
+}
+
+impl From<PromptContext> for PromptContextJson {
+    fn from(context: PromptContext) -> Self {
+        Self {
+            version: PromptContext::JSON_VERSION,
+            keys: context.keys,
+            excluded: context.excluded,
+            custom_keys: context.custom_keys,
+            excluded_custom_keys: context.excluded_custom_keys,
+            period: context.period,
+            amount: context.amount,
+            dates: context.dates,
+            matches: context.matches,
+        }
+    }
+}
+
+impl TryFrom<PromptContextJson> for PromptContext {
+    type Error = String;
+
+    fn try_from(json: PromptContextJson) -> Result<Self, Self::Error> {
+        // Older shapes lack only fields that default
+        if !(1..=PromptContext::JSON_VERSION).contains(&json.version) {
+            return Err(format!(
+                "unsupported PromptContext version {}, expected 1 to {}",
+                json.version,
+                PromptContext::JSON_VERSION
+            ));
+        }
+        Ok(Self {
+            keys: json.keys,
+            excluded: json.excluded,
+            custom_keys: json.custom_keys,
+            excluded_custom_keys: json.excluded_custom_keys,
+            period: json.period,
+            amount: json.amount,
+            dates: json.dates,
+            matches: json.matches,
+        })
+    }
 }
 
 impl PromptContext {
+    /// Version of the JSON shape, bumped whenever it changes since readers reject unknown fields:
+    /// 2 added custom keys, 3 the correction and confidence of matches, 4 year dates
+    pub const JSON_VERSION: u32 = 4;
+
     pub fn new() -> Self {
         Self::default()
     }
@@ -57,6 +258,29 @@ impl PromptContext {
         &self.keys
     }
 
+    /// Keys mentioned only inside a negation: "everything except documents"
+    pub fn excluded_keys(&self) -> &[PromptKey] {
+        &self.excluded
+    }
+
+    pub fn is_excluded(&self, key: PromptKey) -> bool {
+        self.excluded.contains(&key)
+    }
+
+    /// Keys registered with a `Vocabulary`, in vocabulary order
+    pub fn custom_keys(&self) -> &[String] {
+        &self.custom_keys
+    }
+
+    pub fn has_custom_key(&self, name: &str) -> bool {
+        self.custom_keys.iter().any(|key| key == name)
+    }
+
+    /// Registered keys mentioned only inside a negation
+    pub fn excluded_custom_keys(&self) -> &[String] {
+        &self.excluded_custom_keys
+    }
+
     pub fn period(&self) -> Option<Period> {
         self.period
     }
@@ -65,133 +289,705 @@ impl PromptContext {
         self.amount
     }
 
+    /// Explicit and relative dates, in order of appearance
+    pub fn dates(&self) -> &[DateConstraint] {
+        &self.dates
+    }
+
+    /// Every recognised word, ordered by position in the prompt
+    pub fn matches(&self) -> &[TermMatch] {
+        &self.matches
+    }
+
+    /// Occurrences of one key, e.g. to highlight them
+    pub fn key_matches(&self, key: PromptKey) -> impl Iterator<Item = &TermMatch> {
+        self.matches.iter().filter(move |m| m.term == Term::Key(key))
+    }
+
+    /// All periods in order of appearance, "last week with last month" gives `[Week, Month]`
+    pub fn periods(&self) -> Vec<Period> {
+        self.matches
+            .iter()
+            .filter(|m| !m.negated)
+            .filter_map(|m| match m.term {
+                Term::Period(period) => Some(period),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// All amounts in order of appearance
+    pub fn amounts(&self) -> Vec<usize> {
+        self.matches
+            .iter()
+            .filter(|m| !m.negated)
+            .filter_map(|m| match m.term {
+                Term::Amount(amount) => Some(amount),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn has_key(&self, key: PromptKey) -> bool {
         self.keys.contains(&key)
     }
 
-    fn add_key(&mut self, key: PromptKey) {
-        if !self.keys.contains(&key) {
-            self.keys.push(key);
-        }
+    /// Whether all occurrences of the term are negated, `None` when there are none
+    fn negated(&self, term: &Term) -> Option<bool> {
+        let mut occurrences = self.matches.iter().filter(|m| m.term == *term).peekable();
+        occurrences.peek()?;
+        Some(occurrences.all(|m| m.negated))
     }
 
-    fn set_amount(&mut self, num: usize) {
-        self.amount = Some(num);
+    fn add_match(&mut self, term: Term, prompt: &str, span: Range<usize>) {
+        self.matches.push(TermMatch {
+            term,
+            surface: prompt[span.clone()].to_string(),
+            span,
+            negated: false,
+            correction: None,
+            confidence: 1.0,
+        });
     }
 
-    fn set_period(&mut self, period: Period) {
-        self.period = Some(period);
+    fn add_correction(
+        &mut self,
+        term: Term,
+        prompt: &str,
+        span: Range<usize>,
+        correction: String,
+        distance: usize,
+    ) {
+        let surface = prompt[span.clone()].to_string();
+        let confidence = 1.0 - distance as f32 / surface.chars().count() as f32;
+        self.matches.push(TermMatch {
+            term,
+            surface,
+            span,
+            negated: false,
+            correction: Some(correction),
+            confidence,
+        });
+    }
+
+    /// Matches whose word was a typo of a keyword
+    pub fn corrections(&self) -> impl Iterator<Item = &TermMatch> {
+        self.matches.iter().filter(|m| m.correction.is_some())
+    }
+
+    /// Derives keys, period and amount from the matches.
+    /// A key is excluded when all its occurrences are negated.
+    /// Digits win over words for the amount.
+    fn resolve_matches<'a>(&mut self, custom_names: impl Iterator<Item = &'a str>) {
+        self.matches.sort_by_key(|m| (m.span.start, m.span.end));
+        for key in PromptKey::iter() {
+            match self.negated(&Term::Key(key)) {
+                Some(false) => self.keys.push(key),
+                Some(true) => self.excluded.push(key),
+                None => {}
+            }
+        }
+        for name in custom_names {
+            match self.negated(&Term::Custom(name.to_string())) {
+                Some(false) => self.custom_keys.push(name.to_string()),
+                Some(true) => self.excluded_custom_keys.push(name.to_string()),
+                None => {}
+            }
+        }
+        self.period = self.periods().first().copied();
+
+        let amounts: Vec<&TermMatch> = self
+            .matches
+            .iter()
+            .filter(|m| !m.negated && matches!(m.term, Term::Amount(_)))
+            .collect();
+        let first_digits = amounts
+            .iter()
+            .find(|m| m.surface.starts_with(|c: char| c.is_ascii_digit()));
+        self.amount = first_digits
+            .or(amounts.first())
+            .and_then(|m| match m.term {
+                Term::Amount(amount) => Some(amount),
+                _ => None,
+            });
     }
 }
 
-pub struct ContextParser {}
+/// How a period is turned into dates
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum RangeMode {
+    /// Whole calendar units: weeks start on Monday, months/quarters/years on the 1st
+    #[default]
+    Calendar,
+    /// A window of `amount` periods ending today
+    Rolling,
+}
 
-impl ContextParser {
-    pub fn new() -> Self {
-        Self {}
+/// Inclusive range of dates
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DateRange {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        if start <= end {
+            Self { start, end }
+        } else {
+            Self { start: end, end: start }
+        }
     }
 
-    /// Extracts context from prompt
-    pub fn parse(&mut self, lang: &str, prompt: &str) -> Result<PromptContext, ParserError> {
-        let mut context = PromptContext::new();
-        let text_manager = TextManager::new();
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
 
-        for key in PromptKey::iter() {
-            match key {
-                PromptKey::Amount => {
-                    Self::parse_amount(prompt, &mut context, lang, &text_manager)?;
-                }
-                PromptKey::Period => {
-                    Self::parse_period(prompt, &mut context, lang, &text_manager)?;
-                }
-                _ => {
-                    Self::parse_generic_key(key, prompt, &mut context, lang, &text_manager)?;
-                }
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+impl Period {
+    /// First day of the calendar period containing `date`
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+            Period::Quarter => {
+                let month = (date.month0() / 3) * 3 + 1;
+                NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap_or(date)
             }
+            Period::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
         }
+    }
 
-        Ok(context)
+    /// Moves `date` by `count` periods, backwards when `count` is negative
+    pub fn shift(self, date: NaiveDate, count: i64) -> NaiveDate {
+        let (days, months) = match self {
+            Period::Day => (1, 0),
+            Period::Week => (7, 0),
+            Period::Month => (0, 1),
+            Period::Quarter => (0, 3),
+            Period::Year => (0, 12),
+        };
+        let steps = count.unsigned_abs();
+        let shifted = if count >= 0 {
+            date.checked_add_days(Days::new(days * steps))
+                .and_then(|d| d.checked_add_months(Months::new((months * steps) as u32)))
+        } else {
+            date.checked_sub_days(Days::new(days * steps))
+                .and_then(|d| d.checked_sub_months(Months::new((months * steps) as u32)))
+        };
+        shifted.unwrap_or(date)
     }
+}
 
-    fn parse_amount(
-        prompt: &str,
-        context: &mut PromptContext,
-        lang: &str,
-        text_manager: &TextManager,
-    ) -> Result<(), ParserError> {
-        // amount_num must be present
-        let num_patterns = text_manager.split_msg(lang, "amount_num");
-
-        // Parse numeric values from patterns
-        let nums: Vec<usize> = num_patterns
+impl PromptContext {
+    /// Resolves the dates or the period of the prompt into concrete dates.
+    ///
+    /// `now` gives both the reference moment and the timezone in which "today" is taken.
+    /// Explicit dates win over the period; several dates give the range covering all of them.
+    /// For a period, `Last` selects the `amount` periods before the current one, `All` the
+    /// whole current period(s) and `New` (or no key) the current period(s) up to today.
+    /// Returns `None` when the prompt has neither dates nor a period.
+    pub fn date_range<Tz: TimeZone>(&self, now: &DateTime<Tz>, mode: RangeMode) -> Option<DateRange> {
+        let today = now.date_naive();
+        let explicit = self
+            .dates
             .iter()
-            .map(|s| s.parse::<usize>())
-            .collect::<Result<Vec<usize>, _>>()?;
+            .filter_map(|date| date.resolve(today))
+            .reduce(|a, b| DateRange::new(a.start.min(b.start), a.end.max(b.end)));
+        if explicit.is_some() {
+            return explicit;
+        }
+
+        let period = self.period?;
+        let count = self.amount.unwrap_or(1).max(1) as i64;
+
+        let range = match mode {
+            RangeMode::Rolling => {
+                let start = period.shift(today, -count) + Days::new(1);
+                DateRange::new(start, today)
+            }
+            RangeMode::Calendar => {
+                let current = period.start_of(today);
+                if self.has_key(PromptKey::Last) {
+                    let start = period.shift(current, -count);
+                    DateRange::new(start, current - Days::new(1))
+                } else if self.has_key(PromptKey::All) {
+                    let start = period.shift(current, 1 - count);
+                    DateRange::new(start, period.shift(current, 1) - Days::new(1))
+                } else {
+                    DateRange::new(period.shift(current, 1 - count), today)
+                }
+            }
+        };
+        Some(range)
+    }
+}
+
+/// Keyword automatons of one language, built once and shared between parses
+struct Locale {
+    keys: Vec<(PromptKey, KeywordMatcher)>,
+    custom: Vec<(String, KeywordMatcher)>,
+    periods: KeywordMatcher,
+    numbers: NumberWords,
+    dates: DateWords,
+    negations: KeywordMatcher,
+    negation_ends: KeywordMatcher,
+    /// Negation words that may follow what they negate, see [`Locale::negation_scopes`]
+    trailing_negations: Option<KeywordMatcher>,
+    /// Real words [`MatchMode::Fuzzy`] does not correct, see [`Locale::known_words`]
+    known_words: HashSet<String>,
+    /// The word list of the language, "weak" is not a typo of "week"
+    lexicon: Option<&'static HashSet<String>>,
+    stemmer: Option<Stemmer>,
+}
 
-        let pattern_refs: Vec<&str> = num_patterns.iter().map(|s| s.as_str()).collect();
+impl Locale {
+    fn load(lang: &str, text_manager: &TextManager, vocabulary: &Vocabulary) -> Result<Self, ParserError> {
+        let prefix_match = Self::prefix_match(lang, text_manager);
+        let stemmer = Self::stemmer(lang, text_manager)?;
+
+        let keys = PromptKey::iter()
+            .filter(|key| !matches!(key, PromptKey::Period | PromptKey::Amount))
+            .map(|key| {
+                let key_str: &'static str = key.into();
+                let key_lower = format!("{}-words", key_str.to_lowercase());
+                let mut patterns = Self::words(lang, &key_lower, text_manager)?;
+                patterns.extend(vocabulary.builtin_words(key, lang));
+                Self::check_words(&patterns, key_str)?;
+                Ok((key, KeywordMatcher::new(&patterns, prefix_match, stemmer.as_ref())?))
+            })
+            .collect::<Result<Vec<_>, ParserError>>()?;
+
+        vocabulary.check()?;
+        let custom = vocabulary
+            .custom(lang)
+            .map(|(name, words)| {
+                Self::check_words(&words, name)?;
+                Ok((name.to_string(), KeywordMatcher::new(&words, prefix_match, stemmer.as_ref())?))
+            })
+            .collect::<Result<Vec<_>, ParserError>>()?;
+
+        let period_words = Self::words(lang, "period-words", text_manager)?;
+        if period_words.len() != Period::iter().count() {
+            return Err(ParserError::InvalidPattern("period-words".to_string()));
+        }
+        let periods = KeywordMatcher::new(&period_words, prefix_match, stemmer.as_ref())?;
+
+        Ok(Self {
+            keys,
+            custom,
+            periods,
+            numbers: NumberWords::load(text_manager, lang)?,
+            negations: KeywordMatcher::new(
+                &Self::words(lang, "negation-words", text_manager)?,
+                prefix_match,
+                stemmer.as_ref(),
+            )?,
+            negation_ends: KeywordMatcher::new(
+                &Self::words(lang, "negation-end-words", text_manager)?,
+                prefix_match,
+                stemmer.as_ref(),
+            )?,
+            trailing_negations: if text_manager.has_msg(lang, "negation-trailing-words") {
+                Some(KeywordMatcher::new(
+                    &Self::words(lang, "negation-trailing-words", text_manager)?,
+                    prefix_match,
+                    stemmer.as_ref(),
+                )?)
+            } else {
+                None
+            },
+            dates: DateWords::load(text_manager, lang)?,
+            known_words: Self::known_words(lang, text_manager)?,
+            lexicon: lexicon::lexicon(lang),
+            stemmer,
+        })
+    }
 
-        // Find numeric patterns
-        if let Some(found) = aho_parse(&pattern_refs, prompt)? {
-            let num = nums[found.pattern().as_usize()];
-            context.set_amount(num);
-            return Ok(());
+    /// The stop-words of `detect-words` and the negation words, also for a language without a
+    /// word list
+    fn known_words(lang: &str, text_manager: &TextManager) -> Result<HashSet<String>, ParserError> {
+        let mut known = HashSet::new();
+        for msg_id in ["detect-words", "negation-words", "negation-end-words"] {
+            if text_manager.has_msg(lang, msg_id) {
+                known.extend(text_manager.split_msg(lang, msg_id)?.iter().map(|word| fold(word)));
+            }
         }
+        Ok(known)
+    }
 
-        // Text patterns
-        let text_patterns = text_manager.split_msg(lang, "amount_text");
-        let pattern_refs: Vec<&str> = text_patterns.iter().map(|s| s.as_str()).collect();
+    /// Folds the prompt, and stems its words when the locale has a stemmer
+    fn haystack(&self, prompt: &str) -> Folded {
+        match &self.stemmer {
+            Some(stemmer) => Folded::with_stems(prompt, stemmer),
+            None => Folded::new(prompt),
+        }
+    }
 
-        if let Some(found) = aho_parse(&pattern_refs, prompt)? {
-            let index = found.pattern().as_usize();
-            // Use the same numbers from amount_num by index
-            let num = nums.get(index).copied().unwrap_or(index + 1);
-            context.set_amount(num);
+    /// The Snowball algorithm named by `stemmer` and the irregular forms of `lemma-forms`,
+    /// both optional and never taken from the fallback language
+    fn stemmer(lang: &str, text_manager: &TextManager) -> Result<Option<Stemmer>, ParserError> {
+        if !text_manager.has_msg(lang, "stemmer") {
+            return Ok(None);
         }
+        let name = text_manager.get_msg(lang, "stemmer")?;
+        let mut lemmas = Vec::new();
+        if text_manager.has_msg(lang, "lemma-forms") {
+            for pair in text_manager.split_msg(lang, "lemma-forms")? {
+                let (word, lemma) = pair
+                    .split_once(':')
+                    .ok_or_else(|| ParserError::InvalidPattern("lemma-forms".to_string()))?;
+                lemmas.push((word.to_string(), lemma.to_string()));
+            }
+        }
+        Stemmer::new(name.trim(), lemmas)
+            .map(Some)
+            .ok_or_else(|| ParserError::InvalidPattern("stemmer".to_string()))
+    }
+
+    /// A non-empty word list, every word must be more than a bare `*`
+    fn words(lang: &str, msg_id: &str, text_manager: &TextManager) -> Result<Vec<String>, ParserError> {
+        let words = text_manager.split_msg(lang, msg_id)?;
+        Self::check_words(&words, msg_id)?;
+        Ok(words)
+    }
 
+    fn check_words(words: &[String], name: &str) -> Result<(), ParserError> {
+        if words.is_empty() || words.iter().any(|w| w.trim_end_matches('*').is_empty()) {
+            return Err(ParserError::InvalidPattern(name.to_string()));
+        }
         Ok(())
     }
 
-    fn parse_period(
+    /// Byte ranges following a negation word up to the end of the clause
+    /// or a contrast word: "don't compare, just describe" negates only "compare".
+    /// A word of the optional `negation-trailing-words` with none of the `terms` after it
+    /// negates the clause before it instead: German "Vergleiche nicht, beschreibe nur".
+    fn negation_scopes(
+        &self,
         prompt: &str,
-        context: &mut PromptContext,
-        lang: &str,
-        text_manager: &TextManager,
-    ) -> Result<(), ParserError> {
-        let patterns = text_manager.split_msg(lang, "period-words");
-        let pattern_refs: Vec<&str> = patterns.iter().map(|s| s.as_str()).collect();
-
-        if let Some(found) = aho_parse(&pattern_refs, prompt)? {
-            let periods: Vec<Period> = Period::iter().collect();
-            let index = found.pattern().as_usize();
-
-            if index < periods.len() {
-                context.set_period(periods[index]);
+        haystack: &Folded,
+        terms: &[usize],
+    ) -> Vec<Range<usize>> {
+        const CLAUSE_ENDS: &str = ".,;:!?";
+        let ends = self.negation_ends.find_all(haystack);
+        let trailing = self
+            .trailing_negations
+            .as_ref()
+            .map(|matcher| matcher.find_all(haystack))
+            .unwrap_or_default();
+        self.negations
+            .find_all(haystack)
+            .into_iter()
+            .map(|(_, cue)| {
+                let clause_end = prompt[cue.end..]
+                    .find(|c: char| CLAUSE_ENDS.contains(c))
+                    .map_or(prompt.len(), |at| cue.end + at);
+                let contrast = ends
+                    .iter()
+                    .map(|(_, span)| span.start)
+                    .find(|&start| start >= cue.end)
+                    .unwrap_or(prompt.len());
+                let forward = cue.end..clause_end.min(contrast);
+                if !trailing.iter().any(|(_, span)| *span == cue)
+                    || terms.iter().any(|term| forward.contains(term))
+                {
+                    return forward;
+                }
+                let clause_start = prompt[..cue.start]
+                    .rfind(|c: char| CLAUSE_ENDS.contains(c))
+                    .map_or(0, |at| at + 1);
+                let contrast = ends
+                    .iter()
+                    .map(|(_, span)| span.end)
+                    .filter(|&end| end <= cue.start)
+                    .max()
+                    .unwrap_or(0);
+                clause_start.max(contrast)..cue.start
+            })
+            .collect()
+    }
+
+    /// Whether `word*` entries of the locale match as stems, see `match-prefix` in the FTL files
+    fn prefix_match(lang: &str, text_manager: &TextManager) -> bool {
+        text_manager.has_msg(lang, "match-prefix")
+            && text_manager
+                .get_msg(lang, "match-prefix")
+                .is_ok_and(|value| value.trim() == "true")
+    }
+}
+
+/// Pass as language to detect it from the prompt
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// Extracts [`PromptContext`] from prompts.
+///
+/// The keyword automatons of a language are compiled on its first use and cached,
+/// so one parser can be shared between threads and used for every message.
+/// The cache is rebuilt after the messages are reloaded, see [`ContextParser::with_text_manager`].
+/// Languages without vocabulary are an error unless a fallback language is set.
+pub struct ContextParser {
+    text_manager: TextManager,
+    vocabulary: Vocabulary,
+    match_mode: MatchMode,
+    cache: RwLock<Cache>,
+}
+
+/// What the parser built from one [`TextManager::generation`] of the messages
+#[derive(Default)]
+struct Cache {
+    generation: u64,
+    locales: HashMap<String, Arc<Locale>>,
+    detector: Option<Arc<LanguageDetector>>,
+}
+
+impl ContextParser {
+    pub fn new() -> Self {
+        Self {
+            text_manager: TextManager::new().with_fallback(Fallback::Strict),
+            vocabulary: Vocabulary::new(),
+            match_mode: MatchMode::Exact,
+            cache: RwLock::default(),
+        }
+    }
+
+    /// Reads the word lists from `text_manager`, e.g. one made by [`TextManager::from_dir`]
+    /// and reloaded by a [`LocaleWatcher`](crate::lang::LocaleWatcher), instead of the
+    /// embedded locales. The fallback of the parser stays, see [`ContextParser::with_fallback`].
+    pub fn with_text_manager(mut self, text_manager: Arc<TextManager>) -> Self {
+        let fallback = self.text_manager.fallback().clone();
+        self.text_manager = text_manager.as_ref().clone().with_fallback(fallback);
+        self.cache = RwLock::default();
+        self
+    }
+
+    /// Takes missing languages or word lists from another language, e.g. `en`
+    pub fn with_fallback(mut self, fallback: Fallback) -> Self {
+        self.text_manager = self.text_manager.with_fallback(fallback);
+        self.cache = RwLock::default();
+        self
+    }
+
+    /// Registers more keys or more words for the built-in ones, see [`Vocabulary`]
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabulary = std::mem::take(&mut self.vocabulary).merge(vocabulary);
+        self.cache = RwLock::default();
+        self
+    }
+
+    pub fn vocabulary(&self) -> &Vocabulary {
+        &self.vocabulary
+    }
+
+    /// `MatchMode::Fuzzy` also recognises typos, the exact mode is the default
+    pub fn with_match_mode(mut self, match_mode: MatchMode) -> Self {
+        self.match_mode = match_mode;
+        self
+    }
+
+    pub fn match_mode(&self) -> MatchMode {
+        self.match_mode
+    }
+
+    /// Message ids every locale must define, optional ones like `stemmer` are not listed
+    pub fn required_messages() -> Vec<String> {
+        let keys = PromptKey::iter()
+            .filter(|key| !matches!(key, PromptKey::Period | PromptKey::Amount))
+            .map(|key| {
+                let key_str: &'static str = key.into();
+                format!("{}-words", key_str.to_lowercase())
+            });
+        keys
+            .chain(
+                ["negation-words", "negation-end-words"]
+                    .iter()
+                    .chain(NumberWords::MESSAGES)
+                    .chain(DateWords::MESSAGES)
+                    .map(|id| id.to_string()),
+            )
+            .collect()
+    }
+
+    /// Required messages `lang` does not define itself, the fallback is not consulted
+    pub fn missing_messages(&self, lang: &str) -> Vec<String> {
+        Self::required_messages()
+            .into_iter()
+            .filter(|id| !self.text_manager.has_msg(lang, id))
+            .collect()
+    }
+
+    /// The language of the prompt among the locales with vocabulary
+    pub fn detect(&self, prompt: &str) -> Option<Detection> {
+        self.detector().detect(prompt)
+    }
+
+    fn detector(&self) -> Arc<LanguageDetector> {
+        let generation = self.text_manager.generation();
+        if let Some(detector) = self.cached(generation, |cache| cache.detector.clone()) {
+            return detector;
+        }
+        let detector = Arc::new(LanguageDetector::new(&self.text_manager));
+        self.cache(generation, |cache| cache.detector.get_or_insert(detector).clone())
+    }
+
+    /// `lang`, or the detected language for [`AUTO_LANGUAGE`]
+    fn language<'a>(&self, lang: &'a str, prompt: &str) -> Result<Cow<'a, str>, ParserError> {
+        if lang != AUTO_LANGUAGE {
+            return Ok(Cow::Borrowed(lang));
+        }
+        let detection = self.detect(prompt).ok_or(ParserError::UndetectedLanguage)?;
+        Ok(Cow::Owned(detection.language().to_string()))
+    }
+
+    /// Extracts context from prompt, `lang` may be [`AUTO_LANGUAGE`]
+    pub fn parse(&self, lang: &str, prompt: &str) -> Result<PromptContext, ParserError> {
+        let lang = self.language(lang, prompt)?;
+        let locale = self.locale(&lang)?;
+        let haystack = locale.haystack(prompt);
+        let mut context = PromptContext::new();
+
+        for (key, matcher) in &locale.keys {
+            for (_, span) in matcher.find_all(&haystack) {
+                context.add_match(Term::Key(*key), prompt, span);
             }
         }
+        for (name, matcher) in &locale.custom {
+            for (_, span) in matcher.find_all(&haystack) {
+                context.add_match(Term::Custom(name.clone()), prompt, span);
+            }
+        }
+        Self::parse_period(&locale, prompt, &haystack, &mut context);
+        let (dates, date_spans) = locale.dates.parse(prompt, &locale.numbers);
+        context.dates = dates;
+        for (value, span) in locale.numbers.find_all(prompt, &date_spans) {
+            context.add_match(Term::Amount(value), prompt, span);
+        }
+        if self.match_mode == MatchMode::Fuzzy {
+            Self::parse_typos(&locale, prompt, &haystack, &date_spans, &mut context);
+        }
 
-        Ok(())
+        let terms: Vec<usize> = context.matches.iter().map(|m| m.span.start).collect();
+        let scopes = locale.negation_scopes(prompt, &haystack, &terms);
+        for m in &mut context.matches {
+            m.negated = scopes.iter().any(|scope| scope.contains(&m.span.start));
+        }
+        context.resolve_matches(locale.custom.iter().map(|(name, _)| name.as_str()));
+
+        Ok(context)
     }
 
-    fn parse_generic_key(
-        key: PromptKey,
-        prompt: &str,
-        context: &mut PromptContext,
-        lang: &str,
-        text_manager: &TextManager,
-    ) -> Result<(), ParserError> {
-        let key_str: &'static str = key.into();
-        let key_lower = format!("{}-words", key_str.to_lowercase());
-        let patterns = text_manager.split_msg(lang, &key_lower);
+    /// Extracts only the date constraints from prompt
+    pub fn parse_dates(&self, lang: &str, prompt: &str) -> Result<Vec<DateConstraint>, ParserError> {
+        let lang = &*self.language(lang, prompt)?;
+        let generation = self.text_manager.generation();
+        let (dates, _) = match self.cached(generation, |cache| cache.locales.get(lang).cloned()) {
+            Some(locale) => locale.dates.parse(prompt, &locale.numbers),
+            // dates do not need the keyword lists of the locale
+            None => DateWords::load(&self.text_manager, lang)?
+                .parse(prompt, &NumberWords::load(&self.text_manager, lang)?),
+        };
+        Ok(dates)
+    }
 
-        let pattern_refs: Vec<&str> = patterns.iter().map(|s| s.as_str()).collect();
+    /// Looks into the cache unless it was built from another generation of the messages
+    fn cached<T>(&self, generation: u64, get: impl FnOnce(&Cache) -> Option<T>) -> Option<T> {
+        let cache = self.cache.read().unwrap_or_else(PoisonError::into_inner);
+        if cache.generation == generation {
+            get(&cache)
+        } else {
+            None
+        }
+    }
 
-        if aho_parse(&pattern_refs, prompt)?.is_some() {
-            context.add_key(key);
+    /// Stores into the cache of `generation`, emptying it first when it is older.
+    /// A cache already built from newer messages is left alone.
+    fn cache<T>(&self, generation: u64, insert: impl FnOnce(&mut Cache) -> T) -> T {
+        let mut cache = self.cache.write().unwrap_or_else(PoisonError::into_inner);
+        if cache.generation < generation {
+            *cache = Cache { generation, ..Cache::default() };
         }
+        if cache.generation == generation {
+            insert(&mut cache)
+        } else {
+            insert(&mut Cache::default())
+        }
+    }
 
-        Ok(())
+    fn locale(&self, lang: &str) -> Result<Arc<Locale>, ParserError> {
+        let generation = self.text_manager.generation();
+        if let Some(locale) = self.cached(generation, |cache| cache.locales.get(lang).cloned()) {
+            return Ok(locale);
+        }
+
+        let locale = Arc::new(Locale::load(lang, &self.text_manager, &self.vocabulary)?);
+        Ok(self.cache(generation, |cache| {
+            cache.locales.entry(lang.to_string()).or_insert(locale).clone()
+        }))
     }
 
+    /// Corrects words that neither the exact pass nor the dates and numbers recognised
+    fn parse_typos(
+        locale: &Locale,
+        prompt: &str,
+        haystack: &Folded,
+        date_spans: &[Range<usize>],
+        context: &mut PromptContext,
+    ) {
+        let taken: Vec<Range<usize>> = context
+            .matches
+            .iter()
+            .map(|m| m.span.clone())
+            .chain(date_spans.iter().cloned())
+            .collect();
+        for (word, span) in haystack.words() {
+            if taken.iter().any(|t| t.start < span.end && span.start < t.end)
+                || locale.known_words.contains(word)
+                || locale.lexicon.is_some_and(|lexicon| lexicon.contains(word))
+                || locale.numbers.value(word).is_some()
+            {
+                continue;
+            }
+            let keys = locale
+                .keys
+                .iter()
+                .map(|(key, matcher)| (matcher, Term::Key(*key)));
+            let custom = locale
+                .custom
+                .iter()
+                .map(|(name, matcher)| (matcher, Term::Custom(name.clone())));
+            let best = keys
+                .chain(custom)
+                .filter_map(|(matcher, term)| {
+                    let (_, distance, correction) = matcher.closest(word)?;
+                    Some((distance, term, correction))
+                })
+                .chain(locale.periods.closest(word).and_then(|(index, distance, correction)| {
+                    Some((distance, Term::Period(Period::iter().nth(index)?), correction))
+                }))
+                .min_by_key(|(distance, _, _)| *distance);
+            if let Some((distance, term, correction)) = best {
+                context.add_correction(term, prompt, span, correction, distance);
+            }
+        }
+    }
+
+    fn parse_period(locale: &Locale, prompt: &str, haystack: &Folded, context: &mut PromptContext) {
+        let periods: Vec<Period> = Period::iter().collect();
+        for (index, span) in locale.periods.find_all(haystack) {
+            if let Some(&period) = periods.get(index) {
+                context.add_match(Term::Period(period), prompt, span);
+            }
+        }
+    }
 }
 
 impl Default for ContextParser {
@@ -199,16 +995,3 @@ impl Default for ContextParser {
         Self::new()
     }
 }
-
-fn aho_parse(patterns: &[&str], haystack: &str) -> Result<Option<Match>, ParserError> {
-    if patterns.is_empty() {
-        return Ok(None);
-    }
-
-    let ac = AhoCorasick::builder()
-        .match_kind(MatchKind::LeftmostFirst)
-        .build(patterns)
-        .map_err(|e| ParserError::AhoCorasickBuild(e.to_string()))?;
-
-    Ok(ac.find(haystack))
-}
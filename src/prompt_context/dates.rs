@@ -0,0 +1,435 @@
+use super::numbers::NumberWords;
+use super::{DateRange, Period, fold};
+use crate::lang::{TextError, TextManager};
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use strum::IntoEnumIterator;
+
+/// A single point in time mentioned in a prompt.
+/// Partial values get their missing parts from the reference date when resolved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DateValue {
+    /// "2025-12-02", "15.12.2025", "15 December 2025"
+    Date(NaiveDate),
+    /// "15 December", "December 15th", "15.12."
+    DayMonth { day: u32, month: u32 },
+    /// "on the 1st", "am 10."
+    DayOfMonth(u32),
+    /// "in 1999", "since 2020": the whole year
+    Year(i32),
+    /// "Monday"
+    Weekday(Weekday),
+    /// "yesterday" (-1 day), "two weeks ago" (-2 weeks)
+    Relative { offset: i64, period: Period },
+}
+
+/// How a date value restricts the prompt
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DateConstraint {
+    /// "on 15 December", "yesterday"
+    On(DateValue),
+    /// "since Monday", "seit 2025-12-02"
+    Since(DateValue),
+    /// "between the 1st and the 10th", "from Monday to Friday"
+    Between(DateValue, DateValue),
+}
+
+impl DateValue {
+    /// The latest date matching the value, relative to `today`
+    pub fn resolve(&self, today: NaiveDate) -> Option<NaiveDate> {
+        self.resolve_before(today, today)
+    }
+
+    /// The first and last day of the value, one day unless it is a year
+    fn days(&self, today: NaiveDate, anchor: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match *self {
+            DateValue::Year(year) => Some((
+                NaiveDate::from_ymd_opt(year, 1, 1)?,
+                NaiveDate::from_ymd_opt(year, 12, 31)?,
+            )),
+            _ => {
+                let date = self.resolve_before(today, anchor)?;
+                Some((date, date))
+            }
+        }
+    }
+
+    /// The latest date matching the value that is not after `anchor`
+    fn resolve_before(&self, today: NaiveDate, anchor: NaiveDate) -> Option<NaiveDate> {
+        match *self {
+            DateValue::Date(date) => Some(date),
+            DateValue::Year(year) => NaiveDate::from_ymd_opt(year, 12, 31),
+            DateValue::DayMonth { day, month } => (0..=4)
+                .filter_map(|back| NaiveDate::from_ymd_opt(anchor.year() - back, month, day))
+                .find(|date| *date <= anchor),
+            DateValue::DayOfMonth(day) => (0..=12)
+                .filter_map(|back| anchor.checked_sub_months(Months::new(back)))
+                .filter_map(|month| month.with_day(day))
+                .find(|date| *date <= anchor),
+            DateValue::Weekday(weekday) => {
+                let back = (anchor.weekday().num_days_from_monday() + 7
+                    - weekday.num_days_from_monday())
+                    % 7;
+                anchor.checked_sub_days(Days::new(back as u64))
+            }
+            DateValue::Relative { offset, period } => Some(period.shift(today, offset)),
+        }
+    }
+}
+
+impl DateConstraint {
+    /// Resolves the constraint into an inclusive range relative to `today`
+    pub fn resolve(&self, today: NaiveDate) -> Option<DateRange> {
+        match self {
+            DateConstraint::On(value) => {
+                let (start, end) = value.days(today, today)?;
+                Some(DateRange::new(start, end))
+            }
+            DateConstraint::Since(value) => {
+                Some(DateRange::new(value.days(today, today)?.0, today))
+            }
+            DateConstraint::Between(from, to) => {
+                let (_, end) = to.days(today, today)?;
+                let (start, _) = from.days(today, end)?;
+                Some(DateRange::new(start, end))
+            }
+        }
+    }
+}
+
+/// Date vocabulary of one locale
+pub(super) struct DateWords {
+    months: Vec<String>,
+    weekdays: Vec<String>,
+    periods: Vec<String>,
+    today: Vec<String>,
+    yesterday: Vec<String>,
+    ago: Vec<String>,
+    since: Vec<String>,
+    from: Vec<String>,
+    between: Vec<String>,
+    to: Vec<String>,
+    and: Vec<String>,
+    skip: Vec<String>,
+    /// Mark an ordinal as day of month: "on the 3rd", optional
+    on: Vec<String>,
+    /// Mark a year as date: "in 1999", optional
+    within: Vec<String>,
+}
+
+impl DateWords {
+    /// Messages `load` cannot do without
+    pub(super) const MESSAGES: &[&str] = &[
+        "month-words",
+        "weekday-words",
+        "period-words",
+        "date-today-words",
+        "date-yesterday-words",
+        "date-ago-words",
+        "date-since-words",
+        "date-from-words",
+        "date-between-words",
+        "date-to-words",
+        "date-and-words",
+        "date-skip-words",
+    ];
+
+    pub(super) fn load(text_manager: &TextManager, lang: &str) -> Result<Self, TextError> {
+        let words = |msg_id: &str| -> Result<Vec<String>, TextError> {
+            Ok(text_manager
+                .split_msg(lang, msg_id)?
+                .iter()
+                .map(|word| fold(word.trim_end_matches('*')))
+                .collect())
+        };
+        let optional = |msg_id: &str| -> Result<Vec<String>, TextError> {
+            if text_manager.has_msg(lang, msg_id) {
+                words(msg_id)
+            } else {
+                Ok(vec![])
+            }
+        };
+        Ok(Self {
+            months: words("month-words")?,
+            weekdays: words("weekday-words")?,
+            periods: words("period-words")?,
+            today: words("date-today-words")?,
+            yesterday: words("date-yesterday-words")?,
+            ago: words("date-ago-words")?,
+            since: words("date-since-words")?,
+            from: words("date-from-words")?,
+            between: words("date-between-words")?,
+            to: words("date-to-words")?,
+            and: words("date-and-words")?,
+            skip: words("date-skip-words")?,
+            on: optional("date-on-words")?,
+            within: optional("date-in-words")?,
+        })
+    }
+
+    /// Extracts all date constraints from the prompt, in order of appearance,
+    /// together with the byte spans of the recognised dates
+    pub(super) fn parse(
+        &self,
+        prompt: &str,
+        numbers: &NumberWords,
+    ) -> (Vec<DateConstraint>, Vec<Range<usize>>) {
+        let (tokens, token_spans) = tokenize(prompt);
+
+        let mut values: Vec<(usize, DateValue)> = Vec::new();
+        let mut spans = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match self.value_at(&tokens, i, numbers) {
+                Some((value, next)) => {
+                    values.push((i, value));
+                    spans.push(token_spans[i].start..token_spans[next - 1].end);
+                    i = next;
+                }
+                None => i += 1,
+            }
+        }
+
+        let mut constraints = Vec::new();
+        let mut k = 0;
+        while k < values.len() {
+            let (first, value) = values[k];
+            let before = self.word_before(&tokens, first);
+            let opens_range = before.is_some_and(|w| has(&self.between, w) || has(&self.from, w));
+            if opens_range && let Some(&(next_first, next_value)) = values.get(k + 1) {
+                let joined = self
+                    .word_before(&tokens, next_first)
+                    .is_some_and(|w| has(&self.and, w) || has(&self.to, w));
+                if joined {
+                    constraints.push(DateConstraint::Between(value, next_value));
+                    k += 2;
+                    continue;
+                }
+            }
+            if before.is_some_and(|w| has(&self.since, w)) {
+                constraints.push(DateConstraint::Since(value));
+            } else {
+                constraints.push(DateConstraint::On(value));
+            }
+            k += 1;
+        }
+        (constraints, spans)
+    }
+
+    /// Recognises a date value starting at token `i`, returns it with the index after it
+    fn value_at(
+        &self,
+        tokens: &[String],
+        i: usize,
+        numbers: &NumberWords,
+    ) -> Option<(DateValue, usize)> {
+        let token = tokens[i].as_str();
+        let next = tokens.get(i + 1).map(String::as_str);
+
+        if let Some(date) = full_date(token) {
+            return Some((DateValue::Date(date), i + 1));
+        }
+        if let Some((day, month)) = day_month(token) {
+            return Some((DateValue::DayMonth { day, month }, i + 1));
+        }
+        // "15 December [2025]"
+        if let (Some(day), Some(month)) = (day_number(token), next.and_then(|t| self.month(t))) {
+            return Some(self.with_year(tokens, i + 2, day, month));
+        }
+        // "December 15th [2025]"
+        if let (Some(month), Some(day)) = (self.month(token), next.and_then(day_number)) {
+            return Some(self.with_year(tokens, i + 2, day, month));
+        }
+        if let Some(index) = self.weekdays.iter().position(|w| w == token) {
+            let weekday = Weekday::try_from(index as u8).ok()?;
+            return Some((DateValue::Weekday(weekday), i + 1));
+        }
+        if has(&self.today, token) {
+            return Some((relative(0, Period::Day), i + 1));
+        }
+        if has(&self.yesterday, token) {
+            return Some((relative(-1, Period::Day), i + 1));
+        }
+        // "vor zwei Wochen"
+        if has(&self.ago, token)
+            && let Some((amount, period, end)) = self.amount_period(tokens, i + 1, numbers)
+        {
+            return Some((relative(-(amount as i64), period), end));
+        }
+        // "two weeks ago"
+        if let Some((amount, period, end)) = self.amount_period(tokens, i, numbers)
+            && tokens.get(end).is_some_and(|t| has(&self.ago, t))
+        {
+            return Some((relative(-(amount as i64), period), end + 1));
+        }
+        if let Some(day) = ordinal(token) {
+            // "the 3rd of December"
+            if let Some(j) = (i + 1..tokens.len()).find(|&j| !has(&self.skip, &tokens[j]))
+                && let Some(month) = self.month(&tokens[j])
+            {
+                return Some(self.with_year(tokens, j + 1, day, month));
+            }
+            // "since the 1st", "am 10.", but not "the 3rd photo"
+            if self.is_date_context(tokens, i) {
+                return Some((DateValue::DayOfMonth(day), i + 1));
+            }
+        }
+        // "from 1999", "between 1999 and 2003"
+        if let Some(year) = year(token)
+            && (self.is_date_context(tokens, i)
+                || self
+                    .word_before(tokens, i)
+                    .is_some_and(|w| has(&self.within, w)))
+        {
+            return Some((DateValue::Year(year), i + 1));
+        }
+        None
+    }
+
+    fn with_year(&self, tokens: &[String], i: usize, day: u32, month: u32) -> (DateValue, usize) {
+        let year = tokens
+            .get(i)
+            .and_then(|t| t.trim_end_matches('.').parse::<i32>().ok())
+            .filter(|year| (1900..=2100).contains(year));
+        match year.and_then(|year| NaiveDate::from_ymd_opt(year, month, day)) {
+            Some(date) => (DateValue::Date(date), i + 1),
+            None => (DateValue::DayMonth { day, month }, i),
+        }
+    }
+
+    /// "[amount] period" starting at token `i`
+    fn amount_period(
+        &self,
+        tokens: &[String],
+        i: usize,
+        numbers: &NumberWords,
+    ) -> Option<(usize, Period, usize)> {
+        let i = (i..tokens.len()).find(|&j| !has(&self.skip, &tokens[j]))?;
+        let token = &tokens[i];
+        if let Some(period) = self.period(token) {
+            return Some((1, period, i + 1));
+        }
+        let amount = numbers.value(token)?;
+        let period = self.period(tokens.get(i + 1)?)?;
+        Some((amount, period, i + 2))
+    }
+
+    fn month(&self, token: &str) -> Option<u32> {
+        let token = token.trim_end_matches('.');
+        let index = self.months.iter().position(|m| m == token).or_else(|| {
+            (token.chars().count() >= 3)
+                .then(|| self.months.iter().position(|m| m.starts_with(token)))
+                .flatten()
+        })?;
+        Some(index as u32 + 1)
+    }
+
+    fn period(&self, token: &str) -> Option<Period> {
+        let index = self
+            .periods
+            .iter()
+            .position(|p| token.starts_with(p.as_str()))?;
+        Period::iter().nth(index)
+    }
+
+    /// The closest word before token `i` that is not an article
+    fn word_before<'a>(&self, tokens: &'a [String], i: usize) -> Option<&'a str> {
+        tokens[..i]
+            .iter()
+            .rev()
+            .map(String::as_str)
+            .find(|t| !has(&self.skip, t))
+    }
+
+    /// Whether a date word precedes token `i`, articles aside: "since the", "am"
+    fn is_date_context(&self, tokens: &[String], i: usize) -> bool {
+        let before = &tokens[..i];
+        let articles = before
+            .iter()
+            .rev()
+            .take_while(|t| has(&self.skip, t))
+            .count();
+        if before[before.len() - articles..]
+            .iter()
+            .any(|t| has(&self.on, t))
+        {
+            return true;
+        }
+        let Some(word) = before.len().checked_sub(articles + 1).map(|k| &before[k]) else {
+            return false;
+        };
+        [&self.since, &self.from, &self.between, &self.to, &self.and]
+            .iter()
+            .any(|words| has(words, word))
+    }
+}
+
+fn has(words: &[String], token: &str) -> bool {
+    words.iter().any(|w| w == token)
+}
+
+fn relative(offset: i64, period: Period) -> DateValue {
+    DateValue::Relative { offset, period }
+}
+
+/// Folded words and their byte spans
+fn tokenize(prompt: &str) -> (Vec<String>, Vec<Range<usize>>) {
+    let mut tokens = Vec::new();
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (at, c) in prompt.char_indices().chain(std::iter::once((prompt.len(), ' '))) {
+        let separator = c.is_whitespace() || ",;:!?()\"'".contains(c);
+        match (separator, start) {
+            (true, Some(from)) => {
+                tokens.push(fold(&prompt[from..at]));
+                spans.push(from..at);
+                start = None;
+            }
+            (false, None) => start = Some(at),
+            _ => {}
+        }
+    }
+    (tokens, spans)
+}
+
+/// "2025-12-02", "15.12.2025", "15/12/2025"
+fn full_date(token: &str) -> Option<NaiveDate> {
+    let token = token.trim_end_matches('.');
+    ["%Y-%m-%d", "%d.%m.%Y", "%d/%m/%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(token, format).ok())
+}
+
+/// "15.12."
+fn day_month(token: &str) -> Option<(u32, u32)> {
+    let mut parts = token.strip_suffix('.')?.split('.');
+    let day = parts.next()?.parse::<u32>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(2000, month, day).map(|_| (day, month))
+}
+
+/// "1999", a four-digit year
+fn year(token: &str) -> Option<i32> {
+    (token.len() == 4)
+        .then(|| token.parse::<i32>().ok())
+        .flatten()
+        .filter(|year| (1900..=2100).contains(year))
+}
+
+/// "15", "15.", "15th"
+fn day_number(token: &str) -> Option<u32> {
+    ordinal(token).or_else(|| token.parse::<u32>().ok().filter(|d| (1..=31).contains(d)))
+}
+
+/// "1st", "2nd", "3rd", "10th", "10."
+fn ordinal(token: &str) -> Option<u32> {
+    let digits = ["st", "nd", "rd", "th", "."]
+        .iter()
+        .find_map(|suffix| token.strip_suffix(suffix))?;
+    digits.parse::<u32>().ok().filter(|d| (1..=31).contains(d))
+}
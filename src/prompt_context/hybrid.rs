@@ -0,0 +1,311 @@
+use super::{
+    ContextParser, Intent, IntentMatch, IntentResolver, ParserError, Period, PromptContext,
+    PromptKey,
+};
+use rig::completion::CompletionModel;
+use rig::extractor::{ExtractionError, Extractor};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum EntityType {
+    Object,
+    Document,
+    Description,
+    Comparison,
+    Period,
+    Amount,
+    Date,
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct Entity {
+    pub entity_type: EntityType,
+    pub name: String,
+    pub confidence: f32,
+}
+
+/// What the LLM extractor returns
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct ExtractedEntities {
+    pub entities: Vec<Entity>,
+    pub total_count: usize,
+    pub extraction_time: String, // ISO 8601 formatted string
+}
+
+/// Extracts entities with a language model, slow and model-dependent
+pub trait EntityExtractor {
+    fn extract_entities(
+        &self,
+        prompt: &str,
+    ) -> impl Future<Output = Result<ExtractedEntities, ExtractionError>> + Send;
+}
+
+impl<M> EntityExtractor for Extractor<M, ExtractedEntities>
+where
+    M: CompletionModel,
+{
+    fn extract_entities(
+        &self,
+        prompt: &str,
+    ) -> impl Future<Output = Result<ExtractedEntities, ExtractionError>> + Send {
+        let prompt = prompt.to_string();
+        async move { self.extract(prompt).await }
+    }
+}
+
+/// Where a value of the merged context comes from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+pub enum Source {
+    Rules,
+    Llm,
+    /// Both extractors agree
+    Both,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct Provenance {
+    source: Source,
+    confidence: f32,
+}
+
+impl Provenance {
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// 0..=1, rule matches are certain, LLM values keep the model's score
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    fn rules() -> Self {
+        Self {
+            source: Source::Rules,
+            confidence: 1.0,
+        }
+    }
+
+    fn llm(confidence: f32) -> Self {
+        Self {
+            source: Source::Llm,
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The merged result of the rule parser and the LLM extractor
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct HybridContext {
+    context: PromptContext,
+    keys: Vec<(PromptKey, Provenance)>,
+    period: Option<Provenance>,
+    amount: Option<Provenance>,
+    intent: IntentMatch,
+    llm_used: bool,
+    llm_error: Option<String>,
+}
+
+impl HybridContext {
+    /// The merged context, LLM values have no matches in the prompt
+    pub fn context(&self) -> &PromptContext {
+        &self.context
+    }
+
+    pub fn key_provenance(&self, key: PromptKey) -> Option<Provenance> {
+        self.keys
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, provenance)| *provenance)
+    }
+
+    pub fn period_provenance(&self) -> Option<Provenance> {
+        self.period
+    }
+
+    pub fn amount_provenance(&self) -> Option<Provenance> {
+        self.amount
+    }
+
+    /// Intent of the merged context
+    pub fn intent(&self) -> IntentMatch {
+        self.intent
+    }
+
+    /// Whether the LLM extractor was asked
+    pub fn llm_used(&self) -> bool {
+        self.llm_used
+    }
+
+    /// The LLM extractor failed, the result holds the rule values only
+    pub fn llm_error(&self) -> Option<&str> {
+        self.llm_error.as_deref()
+    }
+}
+
+/// Runs `ContextParser` first and asks the LLM extractor only
+/// when the rules find no intent or are not confident about it
+pub struct HybridExtractor<E> {
+    parser: ContextParser,
+    resolver: IntentResolver,
+    extractor: E,
+    min_confidence: f32,
+}
+
+impl<E: EntityExtractor> HybridExtractor<E> {
+    pub fn new(extractor: E) -> Self {
+        Self {
+            parser: ContextParser::new(),
+            resolver: IntentResolver::new(),
+            extractor,
+            min_confidence: 0.6,
+        }
+    }
+
+    pub fn with_parser(mut self, parser: ContextParser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    pub fn with_resolver(mut self, resolver: IntentResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Intent confidence below which the LLM is asked, 0.6 by default
+    pub fn with_min_confidence(mut self, min_confidence: f32) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    pub fn extractor(&self) -> &E {
+        &self.extractor
+    }
+
+    /// `lang` may be [`AUTO_LANGUAGE`](super::AUTO_LANGUAGE), the language of the prompt
+    /// then also applies to the entities of the LLM
+    pub async fn extract(&self, lang: &str, prompt: &str) -> Result<HybridContext, ParserError> {
+        let lang = self.parser.language(lang, prompt)?;
+        let context = self.parser.parse(&lang, prompt)?;
+        let intent = self.resolver.resolve(&context);
+        let mut result = HybridContext {
+            keys: context
+                .keys()
+                .iter()
+                .map(|key| (*key, Provenance::rules()))
+                .collect(),
+            period: context.period().map(|_| Provenance::rules()),
+            amount: context.amount().map(|_| Provenance::rules()),
+            context,
+            intent,
+            llm_used: false,
+            llm_error: None,
+        };
+        if intent.intent() != Intent::Nothing && intent.confidence() >= self.min_confidence {
+            return Ok(result);
+        }
+
+        result.llm_used = true;
+        match self.extractor.extract_entities(prompt).await {
+            Ok(extracted) => {
+                for entity in &extracted.entities {
+                    // One entity the rules cannot read does not spoil the others
+                    if let Err(e) = self.merge(&lang, entity, &mut result) {
+                        tracing::warn!("Skipping entity '{}': {}", entity.name, e);
+                    }
+                }
+                result.intent = self.resolver.resolve(&result.context);
+            }
+            Err(e) => result.llm_error = Some(e.to_string()),
+        }
+        Ok(result)
+    }
+
+    fn merge(
+        &self,
+        lang: &str,
+        entity: &Entity,
+        result: &mut HybridContext,
+    ) -> Result<(), ParserError> {
+        let key = match entity.entity_type {
+            EntityType::Object => Some(PromptKey::Object),
+            EntityType::Document => Some(PromptKey::Document),
+            EntityType::Description => Some(PromptKey::Description),
+            EntityType::Comparison => Some(PromptKey::Comparison),
+            EntityType::Other(_) => None,
+            // "two weeks", "last", "2", "yesterday": the rules know these words
+            EntityType::Period | EntityType::Amount | EntityType::Date => {
+                let words = self.parser.parse(lang, &entity.name)?;
+                for key in words.keys() {
+                    result.add_key(*key, entity.confidence);
+                }
+                if let Some(period) = words.period() {
+                    result.set_period(period, entity.confidence);
+                }
+                if let Some(amount) = words.amount() {
+                    result.set_amount(amount, entity.confidence);
+                }
+                if result.context.dates.is_empty() {
+                    result.context.dates = words.dates;
+                }
+                None
+            }
+        };
+        if let Some(key) = key {
+            result.add_key(key, entity.confidence);
+        }
+        Ok(())
+    }
+}
+
+impl HybridContext {
+    /// Keys the rules saw negated stay excluded
+    fn add_key(&mut self, key: PromptKey, confidence: f32) {
+        if self.context.is_excluded(key) {
+            return;
+        }
+        match self.keys.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, provenance)) if provenance.source == Source::Rules => {
+                provenance.source = Source::Both;
+            }
+            Some(_) => {}
+            None => {
+                self.keys.push((key, Provenance::llm(confidence)));
+                self.context.keys.push(key);
+            }
+        }
+    }
+
+    /// Rule values win, the LLM only fills gaps
+    fn set_period(&mut self, period: Period, confidence: f32) {
+        match (self.context.period, &mut self.period) {
+            (Some(current), Some(provenance))
+                if current == period && provenance.source == Source::Rules =>
+            {
+                provenance.source = Source::Both;
+            }
+            (None, _) => {
+                self.context.period = Some(period);
+                self.period = Some(Provenance::llm(confidence));
+            }
+            _ => {}
+        }
+    }
+
+    fn set_amount(&mut self, amount: usize, confidence: f32) {
+        match (self.context.amount, &mut self.amount) {
+            (Some(current), Some(provenance))
+                if current == amount && provenance.source == Source::Rules =>
+            {
+                provenance.source = Source::Both;
+            }
+            (None, _) => {
+                self.context.amount = Some(amount);
+                self.amount = Some(Provenance::llm(confidence));
+            }
+            _ => {}
+        }
+    }
+}
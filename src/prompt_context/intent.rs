@@ -0,0 +1,212 @@
+use super::{DateConstraint, PromptContext, PromptKey};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use strum_macros::{EnumIter, IntoStaticStr};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IntentError {
+    #[error("Failed to read intent rules: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse intent rules: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Rule {index} has confidence {confidence} outside of 0..=1")]
+    InvalidConfidence { index: usize, confidence: f32 },
+}
+
+/// What the user wants done
+#[derive(
+    Debug, EnumIter, IntoStaticStr, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Intent {
+    /// "Describe the latest image"
+    DescribeLatest,
+    /// "Describe the photo of 15 December"
+    DescribeDate,
+    /// "Compare the images between the 1st and the 10th"
+    CompareTwoDates,
+    /// "What changed during the last two weeks?"
+    CompareLatest,
+    /// "Show all documents"
+    ListDocuments,
+    /// "Create a report"
+    CreateReport,
+    /// Small talk or nothing the rules recognise
+    Nothing,
+}
+
+/// One line of the rule table, a context matches when all conditions hold.
+/// `Period` and `Amount` in the key lists stand for a recognised period or amount.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IntentRule {
+    pub intent: Intent,
+    /// Every key must be present
+    #[serde(default)]
+    pub all: Vec<PromptKey>,
+    /// At least one key must be present, unless empty
+    #[serde(default)]
+    pub any: Vec<PromptKey>,
+    /// No key may be present
+    #[serde(default)]
+    pub none: Vec<PromptKey>,
+    /// Minimum number of dates, "between the 1st and the 10th" counts two
+    #[serde(default)]
+    pub min_dates: usize,
+    pub confidence: f32,
+}
+
+/// The resolved intent with how sure the resolver is about it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IntentMatch {
+    intent: Intent,
+    confidence: f32,
+    rule: Option<usize>,
+}
+
+impl IntentMatch {
+    pub fn intent(&self) -> Intent {
+        self.intent
+    }
+
+    /// 0..=1, the rule confidence lowered by keys the rule does not mention
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Index of the matching rule, `None` for the `Nothing` fallback
+    pub fn rule(&self) -> Option<usize> {
+        self.rule
+    }
+}
+
+/// Maps a `PromptContext` to an `Intent` with a declarative rule table
+#[derive(Debug, Clone)]
+pub struct IntentResolver {
+    rules: Vec<IntentRule>,
+}
+
+impl Default for IntentResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntentResolver {
+    /// The built-in rules from `rules/intents.json`
+    pub fn new() -> Self {
+        Self::from_json(include_str!("../../rules/intents.json"))
+            .expect("Error parsing intent rules")
+    }
+
+    pub fn from_rules(rules: Vec<IntentRule>) -> Result<Self, IntentError> {
+        if let Some((index, rule)) = rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| !(0.0..=1.0).contains(&rule.confidence))
+        {
+            return Err(IntentError::InvalidConfidence {
+                index,
+                confidence: rule.confidence,
+            });
+        }
+        Ok(Self { rules })
+    }
+
+    /// Rule table as a JSON array of `IntentRule`
+    pub fn from_json(json: &str) -> Result<Self, IntentError> {
+        Self::from_rules(serde_json::from_str(json)?)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, IntentError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn rules(&self) -> &[IntentRule] {
+        &self.rules
+    }
+
+    /// The most confident intent, `Nothing` when no rule matches
+    pub fn resolve(&self, context: &PromptContext) -> IntentMatch {
+        self.candidates(context)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| IntentMatch {
+                intent: Intent::Nothing,
+                // nothing recognised at all is most likely small talk
+                confidence: if context.matches().is_empty() && context.dates().is_empty() {
+                    1.0
+                } else {
+                    0.5
+                },
+                rule: None,
+            })
+    }
+
+    /// All matching rules, most confident first, one entry per intent
+    pub fn candidates(&self, context: &PromptContext) -> Vec<IntentMatch> {
+        let mut candidates: Vec<IntentMatch> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.matches(context))
+            .map(|(index, rule)| IntentMatch {
+                intent: rule.intent,
+                confidence: rule.confidence * (0.5 + 0.5 * rule.coverage(context)),
+                rule: Some(index),
+            })
+            .collect();
+        // stable, so earlier rules win ties
+        candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        let mut seen = Vec::new();
+        candidates.retain(|candidate| {
+            let first = !seen.contains(&candidate.intent);
+            seen.push(candidate.intent);
+            first
+        });
+        candidates
+    }
+}
+
+impl IntentRule {
+    fn matches(&self, context: &PromptContext) -> bool {
+        self.all.iter().all(|key| has(context, *key))
+            && (self.any.is_empty() || self.any.iter().any(|key| has(context, *key)))
+            && !self.none.iter().any(|key| has(context, *key))
+            && date_count(context) >= self.min_dates
+    }
+
+    /// Share of the recognised keys the rule mentions
+    fn coverage(&self, context: &PromptContext) -> f32 {
+        let keys = context.keys();
+        if keys.is_empty() {
+            return 1.0;
+        }
+        let mentioned = keys
+            .iter()
+            .filter(|key| self.all.contains(key) || self.any.contains(key))
+            .count();
+        mentioned as f32 / keys.len() as f32
+    }
+}
+
+fn has(context: &PromptContext, key: PromptKey) -> bool {
+    match key {
+        PromptKey::Period => context.period().is_some(),
+        PromptKey::Amount => context.amount().is_some(),
+        _ => context.has_key(key),
+    }
+}
+
+fn date_count(context: &PromptContext) -> usize {
+    context
+        .dates()
+        .iter()
+        .map(|date| match date {
+            DateConstraint::Between(..) => 2,
+            DateConstraint::On(_) | DateConstraint::Since(_) => 1,
+        })
+        .sum()
+}
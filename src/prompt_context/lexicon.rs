@@ -0,0 +1,41 @@
+use super::fold;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Word lists of the embedded locales, one word per line, `#` starts a comment line
+static LEXICONS: [Lexicon; 2] = [
+    Lexicon::new("en", include_str!("../../locales/en.words")),
+    Lexicon::new("de", include_str!("../../locales/de.words")),
+];
+
+/// Real words of a language, folded on first use and shared by all parsers
+struct Lexicon {
+    lang: &'static str,
+    source: &'static str,
+    words: OnceLock<HashSet<String>>,
+}
+
+impl Lexicon {
+    const fn new(lang: &'static str, source: &'static str) -> Self {
+        Self { lang, source, words: OnceLock::new() }
+    }
+
+    fn words(&self) -> &HashSet<String> {
+        self.words.get_or_init(|| {
+            self.source
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(fold)
+                .collect()
+        })
+    }
+}
+
+/// The word list of `lang` or of its nearest parent: "de-CH" uses the German list
+pub(super) fn lexicon(lang: &str) -> Option<&'static HashSet<String>> {
+    crate::lang::parents(lang)
+        .iter()
+        .find_map(|parent| LEXICONS.iter().find(|lexicon| lexicon.lang == parent))
+        .map(Lexicon::words)
+}
@@ -0,0 +1,305 @@
+use super::ParserError;
+use aho_corasick::{AhoCorasick, MatchKind};
+use rust_stemmers::Algorithm;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Lowercases and folds German umlauts and ß, so "Straße", "STRASSE" and "strasse" are equal.
+/// Typographic apostrophes become plain ones: "don’t" is "don't"
+pub(crate) fn fold(text: &str) -> String {
+    Folded::new(text).text
+}
+
+/// Folded text that remembers where each of its bytes came from
+pub(super) struct Folded {
+    text: String,
+    // original byte offset for every folded byte, plus the end
+    offsets: Vec<usize>,
+    // every word replaced by its stem, when the locale has a stemmer
+    stemmed: Option<Box<Folded>>,
+}
+
+impl Folded {
+    pub(super) fn new(original: &str) -> Self {
+        let mut text = String::with_capacity(original.len());
+        let mut offsets = Vec::with_capacity(original.len() + 1);
+        for (at, c) in original.char_indices() {
+            for c in c.to_lowercase() {
+                let before = text.len();
+                match c {
+                    'ä' => text.push_str("ae"),
+                    'ö' => text.push_str("oe"),
+                    'ü' => text.push_str("ue"),
+                    'ß' => text.push_str("ss"),
+                    '’' => text.push('\''),
+                    _ => text.push(c),
+                }
+                offsets.resize(offsets.len() + text.len() - before, at);
+            }
+        }
+        offsets.push(original.len());
+        Self {
+            text,
+            offsets,
+            stemmed: None,
+        }
+    }
+
+    pub(super) fn with_stems(original: &str, stemmer: &Stemmer) -> Self {
+        let mut folded = Self::new(original);
+        folded.stemmed = Some(Box::new(folded.stem(stemmer)));
+        folded
+    }
+
+    /// The same text with every word stemmed, offsets still point into the original
+    fn stem(&self, stemmer: &Stemmer) -> Folded {
+        let mut text = String::with_capacity(self.text.len());
+        let mut offsets = Vec::with_capacity(self.offsets.len());
+        let mut word_start = None;
+        let end = self.text.len();
+        for (at, c) in self.text.char_indices().chain(std::iter::once((end, ' '))) {
+            if at < end && c.is_alphanumeric() {
+                word_start.get_or_insert(at);
+                continue;
+            }
+            if let Some(start) = word_start.take() {
+                let stem = stemmer.stem(&self.text[start..at]);
+                text.push_str(&stem);
+                offsets.resize(offsets.len() + stem.len(), self.offsets[start]);
+            }
+            if at < end {
+                text.push(c);
+                offsets.resize(offsets.len() + c.len_utf8(), self.offsets[at]);
+            }
+        }
+        offsets.push(self.offsets[end]);
+        Folded {
+            text,
+            offsets,
+            stemmed: None,
+        }
+    }
+
+    fn original(&self, span: Range<usize>) -> Range<usize> {
+        self.offsets[span.start]..self.offsets[span.end]
+    }
+
+    /// Folded words with their byte spans in the original text
+    pub(super) fn words(&self) -> impl Iterator<Item = (&str, Range<usize>)> {
+        self.text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let start = word.as_ptr() as usize - self.text.as_ptr() as usize;
+                (word, self.original(start..start + word.len()))
+            })
+    }
+}
+
+/// Snowball stemmer of a locale, irregular forms are mapped to their lemma first:
+/// "verglichen" is stemmed as "vergleichen"
+pub(super) struct Stemmer {
+    algorithm: rust_stemmers::Stemmer,
+    lemmas: HashMap<String, String>,
+}
+
+impl Stemmer {
+    /// `name` is the Snowball algorithm, "english" or "german"
+    pub(super) fn new(name: &str, lemmas: Vec<(String, String)>) -> Option<Self> {
+        let algorithm = match name {
+            "english" => Algorithm::English,
+            "german" => Algorithm::German,
+            _ => return None,
+        };
+        Some(Self {
+            algorithm: rust_stemmers::Stemmer::create(algorithm),
+            lemmas: lemmas
+                .into_iter()
+                .map(|(word, lemma)| (fold(&word), fold(&lemma)))
+                .collect(),
+        })
+    }
+
+    /// Stem of a folded word
+    fn stem(&self, word: &str) -> String {
+        let word = self.lemmas.get(word).map_or(word, String::as_str);
+        self.algorithm.stem(word).into_owned()
+    }
+}
+
+/// Edits a word of `len` characters may contain and still be a typo of a keyword
+fn max_typos(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and transpositions of neighbours ("comapre") cost one edit each
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>(); a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Compiled keyword list of one message, e.g. `comparison-words`
+pub(super) struct KeywordMatcher {
+    // `stem*` patterns, and all patterns when the locale has no stemmer
+    ac: Option<(AhoCorasick, Vec<usize>)>,
+    // dictionary forms matched against the stemmed prompt
+    stemmed_ac: Option<(AhoCorasick, Vec<usize>)>,
+    stems: Vec<bool>,
+    // folded patterns without `*`, for typo-tolerant matching
+    patterns: Vec<Vec<char>>,
+}
+
+impl KeywordMatcher {
+    /// With `prefix_match` a pattern written as `stem*` also matches longer words starting with `stem`.
+    /// With a `stemmer` other patterns match every inflected form: "compare" matches "compared".
+    pub(super) fn new(
+        patterns: &[String],
+        prefix_match: bool,
+        stemmer: Option<&Stemmer>,
+    ) -> Result<Self, ParserError> {
+        let stems: Vec<bool> = patterns
+            .iter()
+            .map(|p| prefix_match && p.ends_with('*'))
+            .collect();
+        let folded: Vec<String> = patterns
+            .iter()
+            .map(|p| fold(p.trim_end_matches('*')))
+            .collect();
+
+        let (raw, dictionary): (Vec<usize>, Vec<usize>) =
+            (0..folded.len()).partition(|&index| stemmer.is_none() || stems[index]);
+        let stemmed: Vec<String> = match stemmer {
+            Some(stemmer) => dictionary
+                .iter()
+                .map(|&index| Folded::new(&folded[index]).stem(stemmer).text)
+                .collect(),
+            None => vec![],
+        };
+        let raw_patterns: Vec<&String> = raw.iter().map(|&index| &folded[index]).collect();
+
+        Ok(Self {
+            ac: Self::build(&raw_patterns)?.map(|ac| (ac, raw)),
+            stemmed_ac: Self::build(&stemmed)?.map(|ac| (ac, dictionary)),
+            stems,
+            patterns: folded.iter().map(|p| p.chars().collect()).collect(),
+        })
+    }
+
+    fn build<P: AsRef<[u8]>>(patterns: &[P]) -> Result<Option<AhoCorasick>, ParserError> {
+        if patterns.iter().all(|p| p.as_ref().is_empty()) {
+            return Ok(None);
+        }
+        // Overlapping search, so a match rejected at a word boundary does not hide the next one
+        AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(patterns)
+            .map(Some)
+            .map_err(|e| ParserError::AhoCorasickBuild(e.to_string()))
+    }
+
+    /// Finds all non-overlapping whole-word matches, ignoring case.
+    /// Returns the pattern index and the byte span in the original text for each match, leftmost first.
+    pub(super) fn find_all(&self, haystack: &Folded) -> Vec<(usize, Range<usize>)> {
+        let mut candidates = Vec::new();
+        if let Some((ac, indices)) = &self.ac {
+            candidates.extend(self.candidates(ac, indices, haystack));
+        }
+        if let (Some((ac, indices)), Some(stemmed)) = (&self.stemmed_ac, &haystack.stemmed) {
+            candidates.extend(self.candidates(ac, indices, stemmed));
+        }
+        candidates.sort_by_key(|(_, span)| (span.start, std::cmp::Reverse(span.end)));
+
+        let mut found = Vec::new();
+        let mut end = 0;
+        for (pattern, span) in candidates {
+            if span.start >= end {
+                end = span.end;
+                found.push((pattern, span));
+            }
+        }
+        found
+    }
+
+    /// Whole-word matches of one automaton with their pattern index and original span
+    fn candidates(
+        &self,
+        ac: &AhoCorasick,
+        indices: &[usize],
+        haystack: &Folded,
+    ) -> Vec<(usize, Range<usize>)> {
+        let text = haystack.text.as_str();
+        ac.find_overlapping_iter(text)
+            .filter(|m| !m.is_empty())
+            .filter_map(|m| {
+                let starts_word = !text[..m.start()]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_alphanumeric);
+                // a stem covers the rest of the word it starts
+                let rest: usize = text[m.end()..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric())
+                    .map(char::len_utf8)
+                    .sum();
+                let pattern = indices[m.pattern().as_usize()];
+                (starts_word && (rest == 0 || self.stems[pattern]))
+                    .then(|| (pattern, haystack.original(m.start()..m.end() + rest)))
+            })
+            .collect()
+    }
+
+    /// The pattern closest to a folded word within the typo budget of its length.
+    /// Returns the pattern index, the edit distance and the corrected word.
+    /// The first letter must match: typos there are rare, and short keywords would collide.
+    pub(super) fn closest(&self, word: &str) -> Option<(usize, usize, String)> {
+        let word: Vec<char> = word.chars().collect();
+        let budget = max_typos(word.len());
+        if budget == 0 {
+            return None;
+        }
+        self.patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| !pattern.is_empty() && pattern[0] == word[0])
+            .filter_map(|(index, pattern)| {
+                // a stem is compared with the start of the word, the rest is kept,
+                // a short stem tolerates as few typos as a short word
+                let budget = budget.min(max_typos(pattern.len()));
+                let lengths = if self.stems[index] {
+                    pattern.len().saturating_sub(budget)..=(pattern.len() + budget).min(word.len())
+                } else {
+                    word.len()..=word.len()
+                };
+                lengths
+                    .map(|len| (edit_distance(&word[..len], pattern), len))
+                    // on ties the longest start, so "wekk" becomes "week" and not "weekk"
+                    .min_by_key(|&(distance, len)| (distance, std::cmp::Reverse(len)))
+                    .filter(|(distance, _)| (1..=budget).contains(distance))
+                    .map(|(distance, len)| {
+                        let corrected: String = pattern.iter().chain(&word[len..]).collect();
+                        (index, distance, corrected)
+                    })
+            })
+            .min_by_key(|(_, distance, _)| *distance)
+    }
+}
@@ -0,0 +1,339 @@
+use super::{ParserError, fold};
+use crate::lang::TextManager;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Building block of a spelled-out number
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Morpheme {
+    /// 0..=9, may be followed by tens after a connector: "zwei|und|zwanzig"
+    Unit(usize),
+    /// 10..=19
+    Teen(usize),
+    /// 20, 30, ..., 90
+    Tens(usize),
+    Hundred,
+    Thousand,
+    /// "and", "und"
+    And,
+    /// Only valid inside a compound word: "ein" in "einundzwanzig"
+    Prefix(usize),
+    /// Ends the number: "third", "dritte"
+    Ordinal(usize),
+}
+
+/// Numeral vocabulary of one locale
+pub(super) struct NumberWords {
+    morphemes: Vec<(String, Morpheme)>,
+    vague: HashMap<String, usize>,
+    ordinal_endings: Vec<String>,
+    /// Words before a spelled-out ordinal that make it count: "the third report"
+    ordinal_articles: Vec<String>,
+}
+
+impl NumberWords {
+    /// Messages `load` cannot do without
+    pub(super) const MESSAGES: &[&str] = &[
+        "number-units",
+        "number-teens",
+        "number-tens",
+        "number-hundred",
+        "number-thousand",
+        "number-and",
+        "number-ordinals",
+        "number-ordinal-articles",
+        "amount-vague",
+    ];
+
+    pub(super) fn load(text_manager: &TextManager, lang: &str) -> Result<Self, ParserError> {
+        let words = |msg_id: &str| -> Result<Vec<String>, ParserError> {
+            Ok(text_manager
+                .split_msg(lang, msg_id)?
+                .iter()
+                .map(|word| fold(word))
+                .collect())
+        };
+        let pairs = |msg_id: &str| -> Result<Vec<(String, usize)>, ParserError> {
+            words(msg_id)?
+                .into_iter()
+                .map(|pair| {
+                    let (word, value) = pair
+                        .split_once(':')
+                        .ok_or_else(|| ParserError::InvalidPattern(msg_id.to_string()))?;
+                    Ok((word.to_string(), value.parse::<usize>()?))
+                })
+                .collect()
+        };
+
+        let mut morphemes = Vec::new();
+        for (index, word) in words("number-units")?.into_iter().enumerate() {
+            morphemes.push((word, Morpheme::Unit(index)));
+        }
+        for (index, word) in words("number-teens")?.into_iter().enumerate() {
+            morphemes.push((word, Morpheme::Teen(index + 10)));
+        }
+        for (index, word) in words("number-tens")?.into_iter().enumerate() {
+            morphemes.push((word, Morpheme::Tens((index + 2) * 10)));
+        }
+        for word in words("number-hundred")? {
+            morphemes.push((word, Morpheme::Hundred));
+        }
+        for word in words("number-thousand")? {
+            morphemes.push((word, Morpheme::Thousand));
+        }
+        for word in words("number-and")? {
+            morphemes.push((word, Morpheme::And));
+        }
+        if text_manager.has_msg(lang, "number-prefixes") {
+            for (word, value) in pairs("number-prefixes")? {
+                morphemes.push((word, Morpheme::Prefix(value)));
+            }
+        }
+        for (word, value) in pairs("number-ordinals")? {
+            morphemes.push((word, Morpheme::Ordinal(value)));
+        }
+        // longest first, so "sechzehn" wins over "sechs"
+        morphemes.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+
+        let ordinal_endings = if text_manager.has_msg(lang, "number-ordinal-endings") {
+            words("number-ordinal-endings")?
+        } else {
+            vec![]
+        };
+
+        Ok(Self {
+            morphemes,
+            vague: pairs("amount-vague")?.into_iter().collect(),
+            ordinal_endings,
+            ordinal_articles: words("number-ordinal-articles")?,
+        })
+    }
+
+    /// Finds all numbers in the prompt, skipping the byte ranges in `skip` (e.g. dates).
+    /// Returns the value and the byte span of each number, leftmost first.
+    pub(super) fn find_all(&self, prompt: &str, skip: &[Range<usize>]) -> Vec<(usize, Range<usize>)> {
+        let tokens: Vec<(String, Range<usize>)> = tokenize(prompt)
+            .into_iter()
+            .filter(|(_, span)| !skip.iter().any(|s| s.start < span.end && span.start < s.end))
+            .collect();
+
+        let mut found = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match self.number_at(&tokens, i) {
+                Some((value, end)) => {
+                    found.push((value, tokens[i].1.start..tokens[end - 1].1.end));
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+        found
+    }
+
+    /// Value of a single word: "12", "zwölf", "zweiundzwanzig"
+    pub(super) fn value(&self, token: &str) -> Option<usize> {
+        digits(token).or_else(|| {
+            let mut number = Number::default();
+            self.segment(token, &mut number).then(|| number.value())
+        })
+    }
+
+    fn number_at(&self, tokens: &[(String, Range<usize>)], i: usize) -> Option<(usize, usize)> {
+        let token = tokens[i].0.as_str();
+        if let Some(value) = digits(token).or_else(|| self.vague.get(token).copied()) {
+            return Some((value, i + 1));
+        }
+
+        let mut number = Number::default();
+        let mut next = Number::default();
+        let mut end = i;
+        for (j, (token, _)) in tokens.iter().enumerate().skip(i) {
+            if !self.segment(token, &mut next) {
+                break;
+            }
+            // "and" only belongs to the number when another number word follows
+            if next.pending_and {
+                continue;
+            }
+            number = next.clone();
+            end = j + 1;
+            if number.finished {
+                break;
+            }
+        }
+        // "first compare, then describe" is no amount, "the first photo" is,
+        // "the first two photos" counts two
+        if number.finished
+            && (!i
+                .checked_sub(1)
+                .is_some_and(|k| self.ordinal_articles.contains(&tokens[k].0))
+                || tokens
+                    .get(end)
+                    .is_some_and(|(next, _)| self.value(next).is_some()))
+        {
+            return None;
+        }
+        (end > i).then(|| (number.value(), end))
+    }
+
+    /// Splits a whole word into morphemes and feeds them to `number`
+    fn segment(&self, token: &str, number: &mut Number) -> bool {
+        if self.segment_from(token, 0, number) {
+            return true;
+        }
+        // inflected ordinals: "dritten", "dritter"
+        self.ordinal_endings.iter().any(|ending| {
+            token.strip_suffix(ending.as_str()).is_some_and(|stem| {
+                let mut inflected = number.clone();
+                let ok = self.segment_from(stem, 0, &mut inflected) && inflected.finished;
+                if ok {
+                    *number = inflected;
+                }
+                ok
+            })
+        })
+    }
+
+    fn segment_from(&self, token: &str, at: usize, number: &mut Number) -> bool {
+        if at == token.len() {
+            return true;
+        }
+        let rest = &token[at..];
+        for (word, morpheme) in &self.morphemes {
+            if !rest.starts_with(word.as_str()) {
+                continue;
+            }
+            let whole = at == 0 && word.len() == token.len();
+            if matches!(morpheme, Morpheme::Prefix(_)) && whole {
+                continue;
+            }
+            let mut next = number.clone();
+            if next.push(*morpheme) && self.segment_from(token, at + word.len(), &mut next) {
+                *number = next;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Running value of a spelled-out number
+#[derive(Debug, Default, Clone)]
+struct Number {
+    total: usize,
+    current: usize,
+    last: Option<Morpheme>,
+    pending_and: bool,
+    started: bool,
+    finished: bool,
+}
+
+impl Number {
+    /// Adds the next morpheme, `false` when it cannot continue the number
+    fn push(&mut self, morpheme: Morpheme) -> bool {
+        if self.finished {
+            return false;
+        }
+        let after_and = std::mem::take(&mut self.pending_and);
+        let small = matches!(
+            self.last,
+            Some(Morpheme::Unit(_) | Morpheme::Teen(_) | Morpheme::Prefix(_) | Morpheme::Ordinal(_))
+        );
+        match morpheme {
+            Morpheme::And => {
+                if !self.started || after_and {
+                    return false;
+                }
+                self.pending_and = true;
+                return true;
+            }
+            Morpheme::Unit(value) | Morpheme::Prefix(value) | Morpheme::Teen(value) => {
+                if small {
+                    return false;
+                }
+                self.current += value;
+            }
+            Morpheme::Tens(value) => {
+                // "twenty two" but also "zwei und zwanzig"
+                if matches!(self.last, Some(Morpheme::Tens(_))) || (small && !after_and) {
+                    return false;
+                }
+                self.current += value;
+            }
+            Morpheme::Ordinal(value) => {
+                if small && !(after_and && value >= 20) {
+                    return false;
+                }
+                self.current = match value {
+                    100 | 1000 => self.current.max(1) * value,
+                    _ => self.current + value,
+                };
+                self.finished = true;
+            }
+            Morpheme::Hundred => {
+                if matches!(self.last, Some(Morpheme::Hundred)) {
+                    return false;
+                }
+                self.current = self.current.max(1) * 100;
+            }
+            Morpheme::Thousand => {
+                if matches!(self.last, Some(Morpheme::Thousand)) {
+                    return false;
+                }
+                self.total += self.current.max(1) * 1000;
+                self.current = 0;
+            }
+        }
+        self.last = Some(morpheme);
+        self.started = true;
+        true
+    }
+
+    fn value(&self) -> usize {
+        self.total + self.current
+    }
+}
+
+/// "12", "100", "3rd", "3.", and grouped by thousands "1,000" or "1.000"
+fn digits(token: &str) -> Option<usize> {
+    let token = ["st", "nd", "rd", "th", "."]
+        .iter()
+        .find_map(|suffix| token.strip_suffix(suffix))
+        .unwrap_or(token);
+    // Up to three digits, then groups of exactly three: "1,000,000"
+    let (first, rest) = token.split_once([',', '.']).unwrap_or((token, ""));
+    let grouped = rest.is_empty()
+        || first.len() <= 3 && rest.split([',', '.']).all(|group| group.len() == 3);
+    let number: String = token.chars().filter(|c| !matches!(c, ',' | '.')).collect();
+    if first.is_empty() || !grouped || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    number.parse::<usize>().ok()
+}
+
+/// Folded words with their byte spans, hyphenated numbers are split: "twenty-two".
+/// A comma followed by three digits groups them: "1,000" but "1,2"
+fn tokenize(prompt: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let bytes = prompt.as_bytes();
+    let digit_at = |at: usize| bytes.get(at).is_some_and(u8::is_ascii_digit);
+    for (at, c) in prompt.char_indices().chain(std::iter::once((prompt.len(), ' '))) {
+        let grouping = c == ','
+            && at > 0
+            && digit_at(at - 1)
+            && (at + 1..at + 4).all(digit_at)
+            && !digit_at(at + 4);
+        let separator = !grouping && (c.is_whitespace() || ",;:!?()\"'-/".contains(c));
+        match (separator, start) {
+            (true, Some(from)) => {
+                tokens.push((fold(&prompt[from..at]), from..at));
+                start = None;
+            }
+            (false, None) => start = Some(at),
+            _ => {}
+        }
+    }
+    tokens
+}
@@ -0,0 +1,151 @@
+use super::{ParserError, PromptKey};
+use fluent_bundle::FluentResource;
+use fluent_syntax::ast::{Entry, PatternElement};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+/// Keywords registered at runtime: new keys like "radiator" or more words for a built-in key.
+///
+/// The JSON shape maps key names to word lists per language,
+/// `{ "radiator": { "en": ["radiator*", "heater*"], "de": ["heizkörper*"] } }`.
+/// A name equal to a built-in key, e.g. "Document", extends its words, "Period" and "Amount"
+/// are reserved. Words of `de` apply to `de-CH` as well.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct Vocabulary {
+    keys: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+impl Vocabulary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_words<I, S>(mut self, name: &str, lang: &str, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.keys
+            .entry(name.to_string())
+            .or_default()
+            .entry(lang.to_string())
+            .or_default()
+            .extend(words.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ParserError> {
+        let vocabulary: Self = serde_json::from_str(json)
+            .map_err(|e| ParserError::InvalidVocabulary(e.to_string()))?;
+        vocabulary.check()?;
+        Ok(vocabulary)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ParserError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| ParserError::InvalidVocabulary(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Every `<name>-words` message of the FTL source becomes a key:
+    /// `radiator-words = radiator* heater*`
+    pub fn from_ftl(lang: &str, source: &str) -> Result<Self, ParserError> {
+        let resource = FluentResource::try_new(source.to_string())
+            .map_err(|(_, errors)| ParserError::InvalidVocabulary(format!("{:?}", errors)))?;
+        let mut vocabulary = Self::new();
+        for entry in resource.entries() {
+            let Entry::Message(message) = entry else {
+                continue;
+            };
+            let (Some(name), Some(pattern)) =
+                (message.id.name.strip_suffix("-words"), &message.value)
+            else {
+                continue;
+            };
+            let text: String = pattern
+                .elements
+                .iter()
+                .filter_map(|element| match element {
+                    PatternElement::TextElement { value } => Some(*value),
+                    PatternElement::Placeable { .. } => None,
+                })
+                .collect();
+            vocabulary = vocabulary.with_words(name, lang, text.split_whitespace());
+        }
+        vocabulary.check()?;
+        Ok(vocabulary)
+    }
+
+    /// Adds the words of `other`, keeping the words already present
+    pub fn merge(mut self, other: Vocabulary) -> Self {
+        for (name, languages) in other.keys {
+            for (lang, words) in languages {
+                self = self.with_words(&name, &lang, words);
+            }
+        }
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+
+    /// Words of `lang` and its parents: `de-CH`, then `de`
+    pub fn words(&self, name: &str, lang: &str) -> Vec<String> {
+        let Some(languages) = self.keys.get(name) else {
+            return Vec::new();
+        };
+        crate::lang::parents(lang)
+            .iter()
+            .filter_map(|parent| languages.get(parent))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Names of keys the parser fills itself are an error rather than ignored
+    pub(super) fn check(&self) -> Result<(), ParserError> {
+        match self.names().find(|name| {
+            matches!(
+                Self::builtin(name),
+                Some(PromptKey::Period | PromptKey::Amount)
+            )
+        }) {
+            Some(name) => Err(ParserError::InvalidVocabulary(format!(
+                "'{}' is a reserved key name",
+                name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// The built-in key a name extends, names compare case-insensitively
+    pub(super) fn builtin(name: &str) -> Option<PromptKey> {
+        PromptKey::iter().find(|key| {
+            let key_str: &'static str = key.into();
+            key_str.eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// Extra words for a built-in key
+    pub(super) fn builtin_words(&self, key: PromptKey, lang: &str) -> Vec<String> {
+        self.keys
+            .iter()
+            .filter(|(name, _)| Self::builtin(name) == Some(key))
+            .flat_map(|(name, _)| self.words(name, lang))
+            .collect()
+    }
+
+    /// Names and words of the keys that are not built-in and have words in `lang`
+    pub(super) fn custom(&self, lang: &str) -> impl Iterator<Item = (&str, Vec<String>)> {
+        self.names()
+            .filter(|name| Self::builtin(name).is_none())
+            .map(move |name| (name, self.words(name, lang)))
+            .filter(|(_, words)| !words.is_empty())
+    }
+}
@@ -10,9 +10,8 @@ pub struct CXError;
 
 impl From<Box<dyn StdError + Send + Sync + 'static>> for CXError {
     #[inline(always)]
-    fn from(b: Box<dyn StdError + Send + Sync + 'static>) -> Self {
-        //b // both sides are the same type
-        b.into()
+    fn from(_b: Box<dyn StdError + Send + Sync + 'static>) -> Self {
+        CXError
     }
 }
 